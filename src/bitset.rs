@@ -1,4 +1,4 @@
-use std::{fmt::Debug, ops::Range};
+use std::{fmt::Debug, num::ParseIntError, ops::Range};
 
 macro_rules! make_bitset {
     (
@@ -150,6 +150,33 @@ macro_rules! make_bitset {
                 self.bits != 0 && self.bits.is_power_of_two()
             }
 
+            /// Returns the number of elements smaller than `i`
+            #[inline]
+            pub const fn rank(&self, i: usize) -> usize {
+                if i >= <$BitType>::BITS as usize {
+                    self.len()
+                } else {
+                    self.intersect(Self::all_less_than(i)).len()
+                }
+            }
+
+            /// Returns the `k`-th smallest element, starting at 0
+            #[inline]
+            pub const fn select(&self, k: usize) -> Option<usize> {
+                if k >= self.len() {
+                    return None;
+                }
+
+                let mut bits = self.bits;
+                let mut i = 0;
+                while i < k {
+                    bits &= bits - 1;
+                    i += 1;
+                }
+
+                Some(bits.trailing_zeros() as usize)
+            }
+
             #[inline]
             pub const fn pop(&mut self) {
                 self.bits = (self.bits - 1) & self.bits;
@@ -189,6 +216,27 @@ macro_rules! make_bitset {
             pub fn iter(&self) -> $IterName {
                 self.into_iter()
             }
+
+            /// Formats the bits as hex with a fixed width, the bytes are in little-endian order
+            /// like in the critical set files
+            pub fn to_hex(self) -> String {
+                format!(
+                    "{:01$x}",
+                    self.bits.swap_bytes(),
+                    <$BitType>::BITS as usize / 4
+                )
+            }
+
+            /// Inverse of `to_hex`
+            pub fn from_hex(hex: &str) -> Result<Self, ParseIntError> {
+                if hex.len() != <$BitType>::BITS as usize / 4 {
+                    // there is no other way to construct a `ParseIntError`
+                    return Err(u8::from_str_radix("", 16).unwrap_err());
+                }
+
+                let bits = <$BitType>::from_str_radix(hex, 16)?;
+                Ok(Self::from_bits(bits.swap_bytes()))
+            }
         }
 
         impl IntoIterator for $Name {
@@ -278,6 +326,15 @@ make_bitset!(u64, BitSet64, BitSet64Iter);
 make_bitset!(u32, BitSet32, BitSet32Iter);
 make_bitset!(u16, BitSet16, BitSet16Iter);
 
+impl BitSet128 {
+    /// Iterates over the `(row, col)` coordinates of the cells of an `n` by `n` square
+    pub fn cells(self, n: usize) -> impl Iterator<Item = (usize, usize)> {
+        assert!(n * n <= 128);
+
+        self.into_iter().map(move |index| (index / n, index % n))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct BitSet256 {
     words: [u64; 4],
@@ -460,6 +517,31 @@ impl BitSet256 {
         self.len() == 1
     }
 
+    /// Returns the number of elements smaller than `i`
+    #[inline]
+    pub fn rank(&self, i: usize) -> usize {
+        if i >= 256 {
+            self.len()
+        } else {
+            self.intersect(Self::all_less_than(i)).len()
+        }
+    }
+
+    /// Returns the `k`-th smallest element, starting at 0
+    #[inline]
+    pub fn select(&self, mut k: usize) -> Option<usize> {
+        for (i, word) in self.words.iter().enumerate() {
+            let count = word.count_ones() as usize;
+            if k < count {
+                let set = BitSet64::from_bits(*word);
+                return set.select(k).map(|index| i * u64::BITS as usize + index);
+            }
+            k -= count;
+        }
+
+        None
+    }
+
     #[inline]
     pub fn pop(&mut self) {
         let old_words = self.words;
@@ -479,19 +561,43 @@ impl BitSet256 {
         *self = self.intersect(Self::from_bits(old_words));
     }
 
-    // #[inline]
-    // pub const fn shift_left(&self, shift: usize) -> Self {
-    //     BitSet256 {
-    //         words: self.words << shift,
-    //     }
-    // }
+    #[inline]
+    pub const fn shift_left(&self, shift: usize) -> Self {
+        let word_shift = shift / u64::BITS as usize;
+        let bit_shift = shift % u64::BITS as usize;
+
+        let mut words = [0; 4];
+        let mut i = 4;
+        while i > word_shift {
+            i -= 1;
+            let source = i - word_shift;
+            words[i] = self.words[source] << bit_shift;
+            if bit_shift != 0 && source > 0 {
+                words[i] |= self.words[source - 1] >> (u64::BITS as usize - bit_shift);
+            }
+        }
+
+        BitSet256 { words }
+    }
+
+    #[inline]
+    pub const fn shift_right(&self, shift: usize) -> Self {
+        let word_shift = shift / u64::BITS as usize;
+        let bit_shift = shift % u64::BITS as usize;
+
+        let mut words = [0; 4];
+        let mut i = 0;
+        while i + word_shift < 4 {
+            let source = i + word_shift;
+            words[i] = self.words[source] >> bit_shift;
+            if bit_shift != 0 && source < 3 {
+                words[i] |= self.words[source + 1] << (u64::BITS as usize - bit_shift);
+            }
+            i += 1;
+        }
 
-    // #[inline]
-    // pub const fn shift_right(&self, shift: usize) -> Self {
-    //     BitSet256 {
-    //         words: self.words >> shift,
-    //     }
-    // }
+        BitSet256 { words }
+    }
 
     pub fn print_sq(&self, size: usize) {
         for i in 0..size {
@@ -575,3 +681,191 @@ impl FromIterator<usize> for BitSet256 {
         BitSet256::from_iter(iter)
     }
 }
+
+/// Returns the mask of the given `(row, col)` cells of an `n` by `n` square
+#[allow(dead_code)]
+pub fn cell_mask(cells: &[(usize, usize)], n: usize) -> BitSet128 {
+    assert!(n * n <= 128);
+
+    cells
+        .iter()
+        .map(|(row, col)| {
+            debug_assert!(*row < n && *col < n);
+            row * n + col
+        })
+        .collect()
+}
+
+/// Returns the mask of all cells of an `n` by `n` square lying in one of `rows` and one of `cols`
+pub fn rows_cols_mask(rows: &[usize], cols: &[usize], n: usize) -> BitSet128 {
+    assert!(n * n <= 128);
+
+    rows.iter()
+        .flat_map(|row| {
+            cols.iter().map(move |col| {
+                debug_assert!(*row < n && *col < n);
+                row * n + col
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn to_u128_pair(bitset: BitSet256) -> [u128; 2] {
+        let words = bitset.bits();
+        [
+            words[0] as u128 | (words[1] as u128) << 64,
+            words[2] as u128 | (words[3] as u128) << 64,
+        ]
+    }
+
+    #[test]
+    fn cells() {
+        let mask = cell_mask(&[(0, 1), (1, 0), (2, 4), (4, 4)], 5);
+        let cells: Vec<_> = mask.cells(5).collect();
+        let manual: Vec<_> = mask.into_iter().map(|i| (i / 5, i % 5)).collect();
+
+        assert_eq!(cells, manual);
+        assert_eq!(cells, vec![(0, 1), (1, 0), (2, 4), (4, 4)]);
+        assert_eq!(BitSet128::empty().cells(5).count(), 0);
+    }
+
+    #[test]
+    fn cell_masks() {
+        let mut mask = BitSet128::empty();
+        mask.insert(0);
+        mask.insert(5);
+        mask.insert(14);
+        assert_eq!(cell_mask(&[(0, 0), (1, 0), (2, 4)], 5), mask);
+        assert_eq!(cell_mask(&[], 5), BitSet128::empty());
+
+        let mut mask = BitSet128::empty();
+        for i in [1, 3] {
+            for j in [0, 2, 3] {
+                mask.insert(i * 4 + j);
+            }
+        }
+        assert_eq!(rows_cols_mask(&[1, 3], &[0, 2, 3], 4), mask);
+        assert_eq!(
+            rows_cols_mask(&[1, 3], &[0, 2, 3], 4),
+            cell_mask(&[(1, 0), (1, 2), (1, 3), (3, 0), (3, 2), (3, 3)], 4)
+        );
+        assert_eq!(
+            rows_cols_mask(&[0, 1, 2], &[0, 1, 2], 3),
+            BitSet128::all_less_than(9)
+        );
+    }
+
+    #[test]
+    fn hex() {
+        let bitset = BitSet128::all_less_than(20);
+        assert_eq!(bitset.to_hex(), "ffff0f00000000000000000000000000");
+        assert_eq!(BitSet128::from_hex(&bitset.to_hex()), Ok(bitset));
+
+        let bitset = BitSet128::single(100);
+        assert_eq!(bitset.to_hex().len(), 32);
+        assert_eq!(BitSet128::from_hex(&bitset.to_hex()), Ok(bitset));
+        assert_eq!(
+            u128::from_le_bytes(std::array::from_fn(|i| {
+                u8::from_str_radix(&bitset.to_hex()[2 * i..2 * i + 2], 16).unwrap()
+            })),
+            bitset.bits()
+        );
+
+        let bitset = BitSet16::single(3);
+        assert_eq!(bitset.to_hex(), "0800");
+        assert_eq!(BitSet16::from_hex("0800"), Ok(bitset));
+
+        assert!(BitSet128::from_hex("ffff").is_err());
+        assert!(BitSet16::from_hex("08g0").is_err());
+    }
+
+    #[test]
+    fn rank_select() {
+        let patterns: [&[usize]; 5] = [
+            &[],
+            &[0],
+            &[1, 5, 6, 31],
+            &[0, 2, 4, 8, 16, 63, 64, 100, 127],
+            &[3, 63, 64, 65, 127, 128, 200, 255],
+        ];
+
+        for pattern in patterns {
+            let bitset = BitSet256::from_slice(pattern);
+            for i in 0..=256 {
+                let expected = pattern.iter().filter(|v| **v < i).count();
+                assert_eq!(bitset.rank(i), expected);
+            }
+            for k in 0..=pattern.len() {
+                assert_eq!(bitset.select(k), pattern.get(k).copied());
+            }
+
+            let pattern: Vec<_> = pattern.iter().copied().filter(|v| *v < 128).collect();
+            let bitset = BitSet128::from_slice(&pattern);
+            for i in 0..=128 {
+                let expected = pattern.iter().filter(|v| **v < i).count();
+                assert_eq!(bitset.rank(i), expected);
+            }
+            for k in 0..=pattern.len() {
+                assert_eq!(bitset.select(k), pattern.get(k).copied());
+            }
+
+            let pattern: Vec<_> = pattern.iter().copied().filter(|v| *v < 16).collect();
+            let bitset = BitSet16::from_slice(&pattern);
+            for i in 0..=16 {
+                let expected = pattern.iter().filter(|v| **v < i).count();
+                assert_eq!(bitset.rank(i), expected);
+            }
+            for k in 0..=pattern.len() {
+                assert_eq!(bitset.select(k), pattern.get(k).copied());
+            }
+        }
+
+        assert_eq!(BitSet128::full().select(127), Some(127));
+        assert_eq!(BitSet128::full().rank(127), 127);
+    }
+
+    #[test]
+    fn shift_256() {
+        let bits = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+        let bitset = BitSet256::from_bits([bits as u64, (bits >> 64) as u64, 0, 0]);
+
+        for shift in [0, 1, 7, 63, 64, 65, 100, 127] {
+            let [low, _] = to_u128_pair(bitset.shift_right(shift));
+            assert_eq!(low, bits >> shift);
+
+            let [low, high] = to_u128_pair(bitset.shift_left(shift));
+            assert_eq!(low, bits << shift);
+            assert_eq!(high, if shift == 0 { 0 } else { bits >> (128 - shift) });
+        }
+
+        assert_eq!(bitset.shift_left(128).shift_right(128), bitset);
+        assert_eq!(
+            bitset.shift_left(200).shift_right(200).bits()[0],
+            bits as u64 & 0xff_ffff_ffff_ffff
+        );
+        assert!(bitset.shift_left(256).is_empty());
+        assert!(bitset.shift_right(256).is_empty());
+        assert!(BitSet256::full().shift_left(255) == BitSet256::single(255));
+        assert!(BitSet256::full().shift_right(255) == BitSet256::single(0));
+    }
+
+    #[test]
+    fn shift_256_matches_contains() {
+        let bitset = BitSet256::from_slice(&[0, 3, 63, 64, 100, 127, 128, 191, 192, 255]);
+
+        for shift in 0..256 {
+            for i in 0..256 {
+                let expected_left = i >= shift && bitset.contains(i - shift);
+                assert_eq!(bitset.shift_left(shift).contains(i), expected_left);
+
+                let expected_right = i + shift < 256 && bitset.contains(i + shift);
+                assert_eq!(bitset.shift_right(shift).contains(i), expected_right);
+            }
+        }
+    }
+}
@@ -173,4 +173,50 @@ impl ConstraintsDyn {
 
         (min_values < n * n + 1).then_some(index)
     }
+
+    /// Returns the empty cell with the fewest candidates together with the number of candidates
+    pub fn most_constrained_cell(&self) -> Option<(usize, usize, usize)> {
+        let n = self.sq.n();
+
+        (0..n)
+            .flat_map(|i| (0..n).map(move |j| (i, j)))
+            .filter(|(i, j)| !self.is_set(*i, *j))
+            .map(|(i, j)| (i, j, self.get_possibilities(i, j).len()))
+            .min_by_key(|(_, _, len)| *len)
+    }
+
+    /// Returns the sum of the number of candidates over all empty cells
+    pub fn total_candidates(&self) -> usize {
+        let n = self.sq.n();
+
+        (0..n)
+            .flat_map(|i| (0..n).map(move |j| (i, j)))
+            .filter(|(i, j)| !self.is_set(*i, *j))
+            .map(|(i, j)| self.get_possibilities(i, j).len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn most_constrained_cell() {
+        let sq = PartialLatinSquareDyn::try_from("0123123023013.12").unwrap();
+        let constraints = ConstraintsDyn::new_partial(&sq);
+
+        assert_eq!(constraints.most_constrained_cell(), Some((3, 1, 1)));
+        assert_eq!(constraints.total_candidates(), 1);
+
+        let empty = ConstraintsDyn::new(4);
+        assert_eq!(empty.most_constrained_cell(), Some((0, 0, 4)));
+        assert_eq!(empty.total_candidates(), 64);
+
+        let full = PartialLatinSquareDyn::try_from("0123123023013012").unwrap();
+        let constraints = ConstraintsDyn::new_partial(&full);
+        assert_eq!(constraints.most_constrained_cell(), None);
+        assert_eq!(constraints.total_candidates(), 0);
+    }
 }
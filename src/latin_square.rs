@@ -1,16 +1,23 @@
 use std::{
     array,
     cmp::Ordering,
+    collections::HashMap,
     fmt::{Debug, Display, Write},
+    ops::{ControlFlow, Range},
+    thread,
+    time::Instant,
 };
 
 use crate::{
-    bitset::{BitSet128, BitSet16, BitSet256},
+    bitset::{rows_cols_mask, BitSet128, BitSet16, BitSet256},
     cycles::{minimize_rows, CYCLE_STRUCTURES},
+    latin_square_dyn::LatinSquareDyn,
+    latin_square_generator::ReducedSquareWalker,
     mols::Mols,
     partial_latin_square::PartialLatinSquare,
     permutation::{Permutation, PermutationIter},
     permutation_dyn::PermutationDyn,
+    progress::progress,
     tuple_iterator::{TupleIterator, TupleIteratorDyn},
 };
 
@@ -26,6 +33,32 @@ impl<const N: usize> LatinSquare<N> {
         LatinSquare { rows: values }
     }
 
+    /// Like `new`, but checks that the rows form a latin square in release builds too
+    pub fn from_rows_checked(rows: [[u8; N]; N]) -> Result<Self, Error> {
+        if Self::is_valid(&rows) {
+            Ok(LatinSquare { rows })
+        } else {
+            Err(Error::NotALatinSquare)
+        }
+    }
+
+    /// Builds the latin square with entries `perm(j) + i mod N`, which is orthogonal to the
+    /// cyclic latin square if `perm` is a complete mapping. Returns `None` otherwise
+    pub fn from_complete_mapping(perm: &Permutation<N>) -> Option<Self> {
+        if !perm.is_complete_mapping() {
+            return None;
+        }
+
+        let rows = array::from_fn(|i| array::from_fn(|j| ((perm.apply(j) + i) % N) as u8));
+
+        Some(Self::new(rows))
+    }
+
+    /// Builds a latin square whose `i`-th row is the `i`-th permutation
+    pub fn from_permutations(perms: [Permutation<N>; N]) -> Result<Self, Error> {
+        Self::from_rows_checked(perms.map(|perm| perm.into_array().map(|val| val as u8)))
+    }
+
     pub fn get(&self, row: usize, col: usize) -> usize {
         self.rows[row][col].into()
     }
@@ -75,6 +108,16 @@ impl<const N: usize> LatinSquare<N> {
         val
     }
 
+    /// Returns for each value `v` the permutation mapping each row to the column containing `v`
+    pub fn value_permutations(&self) -> [Permutation<N>; N] {
+        array::from_fn(|v| {
+            let cols = self.get_val(v).map(|col| col as usize);
+            debug_assert!(cols.iter().copied().collect::<BitSet16>() == BitSet16::all_less_than(N));
+
+            Permutation::from_array(cols)
+        })
+    }
+
     pub fn to_values(self) -> [[u8; N]; N] {
         self.rows
     }
@@ -116,6 +159,62 @@ impl<const N: usize> LatinSquare<N> {
         true
     }
 
+    /// Swaps rows and columns, equivalent to the (C, R, S) conjugate
+    pub fn transpose(&self) -> Self {
+        LatinSquare {
+            rows: array::from_fn(|i| self.get_col(i)),
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        *self == self.transpose()
+    }
+
+    /// Checks whether both the main diagonal and the anti-diagonal are transversals
+    pub fn is_diagonal(&self) -> bool {
+        let diagonal: BitSet16 = (0..N).map(|i| self.get(i, i)).collect();
+        let anti_diagonal: BitSet16 = (0..N).map(|i| self.get(i, N - 1 - i)).collect();
+
+        diagonal == BitSet16::all_less_than(N) && anti_diagonal == BitSet16::all_less_than(N)
+    }
+
+    /// Checks whether `(a * b) * c == a * (b * c)` holds for all elements,
+    /// where `a * b` is the entry in row `a` and column `b`
+    pub fn is_associative(&self) -> bool {
+        for a in 0..N {
+            for b in 0..N {
+                let ab = self.get(a, b);
+                for c in 0..N {
+                    if self.get(ab, c) != self.get(a, self.get(b, c)) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Returns the element `e` with `e * a == a * e == a` for all `a`, if it exists
+    pub fn group_identity(&self) -> Option<usize> {
+        (0..N).find(|e| (0..N).all(|a| self.get(*e, a) == a && self.get(a, *e) == a))
+    }
+
+    /// The square as a binary operation `a * b`
+    pub fn op(&self, a: usize, b: usize) -> usize {
+        self.get(a, b)
+    }
+
+    /// Returns the permutation `x -> a * x`
+    pub fn left_translation(&self, a: usize) -> Permutation<N> {
+        Permutation::from_array(array::from_fn(|x| self.op(a, x)))
+    }
+
+    /// Returns the permutation `x -> x * b`
+    pub fn right_translation(&self, b: usize) -> Permutation<N> {
+        Permutation::from_array(array::from_fn(|x| self.op(x, b)))
+    }
+
     pub fn is_reduced(&self) -> bool {
         for i in 0..N {
             if self.rows[0][i] != i as u8 || self.rows[i][0] != i as u8 {
@@ -125,8 +224,126 @@ impl<const N: usize> LatinSquare<N> {
         true
     }
 
+    /// Returns all reduced squares in lexicographic order of their rows
+    pub fn enumerate_reduced() -> impl Iterator<Item = Self> {
+        // the walker fills the cells in row-major order with ascending values
+        ReducedSquareWalker::new()
+    }
+
+    /// Returns the reduced square at position `index` in `enumerate_reduced`
+    pub fn from_reduced_index(mut index: usize) -> Option<Self> {
+        let mut completions = ReducedCompletions::new();
+
+        let mut rows = [[0; N]; N];
+        rows[0] = array::from_fn(|i| i as u8);
+        let mut used = ReducedCompletions::add_row(&[BitSet16::empty(); N], &rows[0]);
+
+        if index >= completions.count(&used) {
+            return None;
+        }
+
+        for (i, row) in rows.iter_mut().enumerate().skip(1) {
+            for candidate in ReducedCompletions::rows(i, &used) {
+                let next_used = ReducedCompletions::add_row(&used, &candidate);
+                let count = completions.count(&next_used);
+
+                if index < count {
+                    *row = candidate;
+                    used = next_used;
+                    break;
+                }
+                index -= count;
+            }
+        }
+
+        Some(LatinSquare::new(rows))
+    }
+
+    /// Returns the position of `self` in `enumerate_reduced`, or `None` if `self` is not reduced
+    pub fn reduced_index(&self) -> Option<usize> {
+        if !self.is_reduced() {
+            return None;
+        }
+
+        let mut completions = ReducedCompletions::new();
+        let mut used = ReducedCompletions::add_row(&[BitSet16::empty(); N], &self.rows[0]);
+
+        let mut index = 0;
+        for i in 1..N {
+            for candidate in ReducedCompletions::rows(i, &used) {
+                let next_used = ReducedCompletions::add_row(&used, &candidate);
+
+                if candidate == self.rows[i] {
+                    used = next_used;
+                    break;
+                }
+                index += completions.count(&next_used);
+            }
+        }
+
+        Some(index)
+    }
+
+    /// Relabels the symbols so that the first row is in natural order
+    /// and then sorts the rows by their first entry
+    pub fn reduced(&self) -> Self {
+        self.reduced_with_perms().0
+    }
+
+    /// Same as `reduced`, but also returns the row, column and symbol permutations `[rows, cols, vals]`
+    /// such that `self.permuted_rows(rows).permuted_cols(cols).permuted_vals(vals)` is the reduced square
+    pub fn reduced_with_perms(&self) -> (Self, [Permutation<N>; 3]) {
+        let vals = Permutation::from_array(self.rows[0].map(|v| v as usize)).inverse();
+        let sq = self.permuted_vals(&vals);
+
+        let rows = Permutation::from_array(sq.get_col(0).map(|v| v as usize));
+        let sq = sq.permuted_rows(&rows);
+
+        debug_assert!(sq.is_reduced());
+        (sq, [rows, Permutation::identity(), vals])
+    }
+
     pub fn num_transversals(&self) -> usize {
-        self.transversals_bitset().len()
+        if N * N <= 128 {
+            self.transversals_bitset().len()
+        } else {
+            self.transversals_bitset256().len()
+        }
+    }
+
+    /// Same as `num_transversals`, but looks up and stores the result in `cache`
+    pub fn num_transversals_cached(&self, cache: &mut HashMap<Self, usize>) -> usize {
+        *cache
+            .entry(*self)
+            .or_insert_with(|| self.num_transversals())
+    }
+
+    /// Same as `num_transversals`, but the search is split by the cell
+    /// chosen in the first row across `max_threads` threads
+    pub fn num_transversals_threaded(&self, max_threads: usize) -> usize {
+        let max_threads = max_threads.clamp(1, N);
+        let chunk_size = N.div_ceil(max_threads);
+
+        thread::scope(|scope| {
+            let threads: Vec<_> = (0..N)
+                .step_by(chunk_size)
+                .map(|start| {
+                    let first = start..(start + chunk_size).min(N);
+                    scope.spawn(move || {
+                        if N * N <= 128 {
+                            self.transversals_bitset_in(first).len()
+                        } else {
+                            self.transversals_bitset256_in(first).len()
+                        }
+                    })
+                })
+                .collect();
+
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .sum()
+        })
     }
 
     const BITSET_COLS: [BitSet128; N] = {
@@ -190,10 +407,17 @@ impl<const N: usize> LatinSquare<N> {
     };
 
     pub fn transversals_bitset(&self) -> Vec<BitSet128> {
+        self.transversals_bitset_in(0..N)
+    }
+
+    /// Returns the transversals whose entry in the first row is the `i`-th
+    /// by value for some `i` in `first`
+    fn transversals_bitset_in(&self, first: Range<usize>) -> Vec<BitSet128> {
         assert!(N * N <= 128);
         assert!(N <= 16);
 
         let mut indices = [0; N];
+        indices[0] = first.start;
 
         let mut bitsets = Vec::new();
 
@@ -214,7 +438,7 @@ impl<const N: usize> LatinSquare<N> {
 
         let value_bitsets = value_bitsets;
 
-        'l: loop {
+        'l: while indices[0] < first.end {
             let mut unused_vals = BitSet16::all_less_than(N);
             let mut bitset = BitSet128::empty();
 
@@ -263,10 +487,15 @@ impl<const N: usize> LatinSquare<N> {
     }
 
     pub fn transversals_bitset256(&self) -> Vec<BitSet256> {
+        self.transversals_bitset256_in(0..N)
+    }
+
+    fn transversals_bitset256_in(&self, first: Range<usize>) -> Vec<BitSet256> {
         assert!(N * N <= 256);
         assert!(N <= 16);
 
         let mut indices = [0; N];
+        indices[0] = first.start;
 
         let mut bitsets = Vec::new();
 
@@ -287,7 +516,7 @@ impl<const N: usize> LatinSquare<N> {
 
         let value_bitsets = value_bitsets;
 
-        'l: loop {
+        'l: while indices[0] < first.end {
             let mut unused_vals = BitSet16::all_less_than(N);
             let mut bitset = BitSet256::empty();
 
@@ -336,6 +565,11 @@ impl<const N: usize> LatinSquare<N> {
     }
 
     pub fn max_disjoint_transversals(&self) -> usize {
+        self.max_disjoint_transversal_cover().len()
+    }
+
+    /// Returns a largest set of disjoint transversals found by `max_disjoint_transversals`
+    pub fn max_disjoint_transversal_cover(&self) -> Vec<BitSet128> {
         let mut transversals_by_start = [(); N].map(|_| Vec::new());
 
         for t in self.transversals_bitset() {
@@ -347,44 +581,80 @@ impl<const N: usize> LatinSquare<N> {
             transversals_by_start[first].push(t);
         }
 
-        transversals_by_start[0]
-            .iter()
-            .map(|transversal| {
-                let mut disjoint = vec![*transversal];
-                let mut max_len = 1;
+        let mut max_disjoint = Vec::new();
+
+        for transversal in &transversals_by_start[0] {
+            let mut disjoint = vec![*transversal];
+            if max_disjoint.is_empty() {
+                max_disjoint = disjoint.clone();
+            }
 
-                let mut indices = vec![0];
+            let mut indices = vec![0];
 
-                'i: while let Some(index) = indices.last_mut() {
-                    let i = disjoint.len();
+            'i: while let Some(index) = indices.last_mut() {
+                let i = disjoint.len();
 
-                    for other in transversals_by_start[i].iter().skip(*index) {
-                        *index += 1;
+                for other in transversals_by_start[i].iter().skip(*index) {
+                    *index += 1;
 
-                        let is_disjoint = disjoint.iter().all(|t| other.is_disjoint(*t));
+                    let is_disjoint = disjoint.iter().all(|t| other.is_disjoint(*t));
 
-                        if is_disjoint {
-                            disjoint.push(*other);
-                            max_len = max_len.max(disjoint.len());
-                            if max_len == N {
-                                return max_len;
+                    if is_disjoint {
+                        disjoint.push(*other);
+                        if disjoint.len() > max_disjoint.len() {
+                            max_disjoint = disjoint.clone();
+                            if max_disjoint.len() == N {
+                                return max_disjoint;
                             }
-                            indices.push(0);
-                            continue 'i;
                         }
+                        indices.push(0);
+                        continue 'i;
                     }
-
-                    indices.pop();
-                    disjoint.pop();
                 }
 
-                max_len
-            })
-            .max()
-            .unwrap_or(0)
+                indices.pop();
+                disjoint.pop();
+            }
+        }
+
+        max_disjoint
     }
 
     pub fn full_disjoint_transversals_bitset(&self) -> Vec<[BitSet128; N]> {
+        let mut disjoint_transversals = Vec::new();
+        self.for_each_full_disjoint_transversals(|disjoint| {
+            disjoint_transversals.push(*disjoint);
+            ControlFlow::Continue(())
+        });
+        disjoint_transversals
+    }
+
+    /// Returns whether `self` has an orthogonal mate, stopping at the first one found
+    pub fn has_orthogonal_mate(&self) -> bool {
+        let mut found = false;
+        self.for_each_full_disjoint_transversals(|_| {
+            found = true;
+            ControlFlow::Break(())
+        });
+        found
+    }
+
+    /// Returns the number of orthogonal mates without constructing them, equal to
+    /// `self.orthogonal_squares().count()`
+    pub fn num_orthogonal_mates(&self) -> usize {
+        let mut count = 0;
+        self.for_each_full_disjoint_transversals(|_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        count
+    }
+
+    /// Calls `f` for each decomposition into disjoint transversals until it returns `Break`
+    fn for_each_full_disjoint_transversals(
+        &self,
+        mut f: impl FnMut(&[BitSet128; N]) -> ControlFlow<()>,
+    ) {
         let mut transversals_by_start: [[Vec<_>; N]; N] =
             array::from_fn(|_| array::from_fn(|_| Vec::new()));
 
@@ -405,7 +675,7 @@ impl<const N: usize> LatinSquare<N> {
             transversals_by_start[first][second].push(t);
         }
 
-        let mut disjoint_transversals = Vec::new();
+        let mut found = 0usize;
 
         for i in 0..N {
             for transversal in &transversals_by_start[0][i] {
@@ -433,9 +703,14 @@ impl<const N: usize> LatinSquare<N> {
 
                         if transversals_by_start[N - 1][second_row].contains(&left) {
                             disjoint[N - 1] = left;
-                            disjoint_transversals.push(disjoint);
-                            if disjoint_transversals.len() % 1000 == 0 {
-                                dbg!(disjoint_transversals.len());
+                            if f(&disjoint).is_break() {
+                                return;
+                            }
+                            found += 1;
+                            if found % 1000 == 0 {
+                                progress(format_args!(
+                                    "n = {N}: {found} disjoint transversal sets"
+                                ));
                             }
                         }
                     } else {
@@ -468,8 +743,6 @@ impl<const N: usize> LatinSquare<N> {
                 }
             }
         }
-
-        disjoint_transversals
     }
 
     pub fn orthogonal_squares(&self) -> impl Iterator<Item = LatinSquare<N>> + '_ {
@@ -498,6 +771,15 @@ impl<const N: usize> LatinSquare<N> {
     }
 
     pub fn mols(&self, lookup: &[Vec<(Permutation<N>, Permutation<N>)>]) -> Vec<Mols<N>> {
+        self.mols_with_deadline(lookup, None)
+    }
+
+    /// Like `mols`, but stops at `deadline` and returns the sets of MOLS found until then
+    pub fn mols_with_deadline(
+        &self,
+        lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
+        deadline: Option<Instant>,
+    ) -> Vec<Mols<N>> {
         let transversals = self.transversals_bitset();
 
         let mut indices = vec![0];
@@ -508,6 +790,14 @@ impl<const N: usize> LatinSquare<N> {
         let mut all_mols = Vec::new();
 
         'i: while let Some(index) = indices.last_mut() {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                progress(format_args!(
+                    "n = {N}: timeout after {} mols",
+                    all_mols.len()
+                ));
+                break;
+            }
+
             for disjoint_transversal in disjoint_transversals.last().unwrap().iter().skip(*index) {
                 let sq = Self::bitset_transversals_to_sq(&disjoint_transversal);
 
@@ -521,7 +811,11 @@ impl<const N: usize> LatinSquare<N> {
                     if !all_mols.contains(&new_mols) {
                         all_mols.push(new_mols);
                         if all_mols.len() % 1000 == 0 {
-                            dbg!(&indices, all_mols.len());
+                            progress(format_args!(
+                                "n = {N}: {} mols, depth {}",
+                                all_mols.len(),
+                                indices.len()
+                            ));
                         }
                     }
                 }
@@ -546,23 +840,58 @@ impl<const N: usize> LatinSquare<N> {
         all_mols
     }
 
+    /// Returns a set of mutually orthogonal latin squares containing `self`.
+    /// The set is extended by the first mate orthogonal to all squares found so far,
+    /// so this is only a lower bound and not necessarily a maximal set
+    pub fn greedy_mols(&self) -> Vec<LatinSquare<N>> {
+        let mut mols = vec![*self];
+        let mut transversals = self.transversals_bitset();
+
+        while let Some(disjoint_transversals) = first_disjoint_transversals_bitset(&transversals) {
+            let sq = Self::bitset_transversals_to_sq(&disjoint_transversals);
+
+            let new_transversals = sq.transversals_bitset();
+            transversals.retain(|t| new_transversals.contains(t));
+
+            mols.push(sq);
+        }
+
+        mols
+    }
+
     pub fn kmols(
         &self,
         k: usize,
         _lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
     ) -> Vec<Mols<N>> {
-        let transversals = self.transversals_bitset();
+        Self::extend_mols(&[*self], k)
+    }
+
+    /// Returns all sets of `k` mutually orthogonal latin squares which start with `sqs`.
+    /// `sqs` has to be non-empty and its squares have to be mutually orthogonal
+    pub fn extend_mols(sqs: &[Self], k: usize) -> Vec<Mols<N>> {
+        debug_assert!(!sqs.is_empty());
+
+        if sqs.len() >= k {
+            return vec![Mols::new_unchecked(sqs.to_vec())];
+        }
+
+        let mut transversals = sqs[0].transversals_bitset();
+        for sq in &sqs[1..] {
+            let new_transversals = sq.transversals_bitset();
+            transversals.retain(|t| new_transversals.contains(t));
+        }
 
         let mut indices = vec![0];
-        let mut current_mols = vec![*self];
+        let mut current_mols = sqs.to_vec();
         let mut disjoint_transversals = vec![n_disjoint_transversals_bitset(&transversals)];
-        let mut intersections = vec![transversals.clone()];
+        let mut intersections = vec![transversals];
 
         let mut all_mols = Vec::new();
 
         'i: while let Some(index) = indices.last_mut() {
             for disjoint_transversal in disjoint_transversals.last().unwrap().iter().skip(*index) {
-                let sq = Self::bitset_transversals_to_sq(&disjoint_transversal);
+                let sq = Self::bitset_transversals_to_sq(disjoint_transversal);
 
                 *index += 1;
 
@@ -573,7 +902,7 @@ impl<const N: usize> LatinSquare<N> {
 
                     all_mols.push(new_mols);
                     if all_mols.len() % 1000 == 0 {
-                        dbg!(all_mols.len());
+                        progress(format_args!("n = {N}: {} mols", all_mols.len()));
                     }
 
                     current_mols.pop();
@@ -602,15 +931,19 @@ impl<const N: usize> LatinSquare<N> {
 
     /// Counts how many rows are the same until a differing row is found
     pub fn num_same_rows(&self, other: &Self) -> usize {
-        for i in 0..N {
-            for j in 0..N {
-                if self.get(i, j) != other.get(i, j) {
-                    return i;
-                }
-            }
-        }
+        (0..N)
+            .find(|i| self.rows[*i] != other.rows[*i])
+            .unwrap_or(N)
+    }
 
-        N
+    /// Returns the number of cells in which `self` and `other` differ
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        self.rows
+            .iter()
+            .flatten()
+            .zip(other.rows.iter().flatten())
+            .filter(|(a, b)| a != b)
+            .count()
     }
 
     /// returns all permutations of rows, columns and values
@@ -618,6 +951,15 @@ impl<const N: usize> LatinSquare<N> {
         PermutationIter::new().map(|perm| self.permuted_rcs(&perm))
     }
 
+    /// Returns the conjugate with the given name, see `Permutation::from_rcs_name`
+    pub fn conjugate(&self, name: &str) -> Result<Self, Error> {
+        let rcs = Permutation::from_rcs_name(name).ok_or_else(|| Error::InvalidConjugate {
+            name: name.to_string(),
+        })?;
+
+        Ok(self.permuted_rcs(&rcs))
+    }
+
     fn isotopy_class_permutation(&self) -> (Self, [Permutation<N>; 3]) {
         let mut candidates = Vec::new();
         let mut min_cycles = vec![N];
@@ -815,6 +1157,24 @@ impl<const N: usize> LatinSquare<N> {
         self.isotopy_class_permutation().0
     }
 
+    /// Same as `isotopy_class_lookup`, but returns `None` if `self` already is the representative
+    pub fn isotopy_class_if_not_reduced(
+        &self,
+        lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
+    ) -> Option<Self> {
+        let isotopy_class = self.isotopy_class_lookup(lookup);
+        (isotopy_class != *self).then_some(isotopy_class)
+    }
+
+    /// Same as `isotopy_class_permutations`, but returns `None` if `self` already is the representative
+    pub fn isotopy_class_permutations_if_not_reduced(
+        &self,
+        lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
+    ) -> Option<(Self, Vec<[Permutation<N>; 3]>)> {
+        let (isotopy_class, permutations) = self.isotopy_class_permutations(lookup);
+        (isotopy_class != *self).then_some((isotopy_class, permutations))
+    }
+
     pub fn isotopy_class_lookup(&self, lookup: &[Vec<(Permutation<N>, Permutation<N>)>]) -> Self {
         let mut candidates = Vec::with_capacity(N * N);
         let mut min_cycle_index = CYCLE_STRUCTURES[N].len() - 1;
@@ -926,6 +1286,40 @@ impl<const N: usize> LatinSquare<N> {
         symmetries
     }
 
+    /// Returns all triples of row, column and symbol permutations that map the square to itself
+    pub fn autotopisms(
+        &self,
+        lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
+    ) -> Vec<[Permutation<N>; 3]> {
+        let (_, permutations) = self.isotopy_class_permutations(lookup);
+
+        // any isotopism to the isotopy class followed by the inverse of a fixed one is an autotopism
+        let inverse = permutations[0].clone().map(|p| p.inverse());
+
+        let mut autotopisms: Vec<_> = permutations
+            .into_iter()
+            .map(|mut perm| {
+                for (p, inverse) in perm.iter_mut().zip(&inverse) {
+                    *p = p.then(inverse);
+                }
+                perm
+            })
+            .collect();
+
+        autotopisms.sort();
+        autotopisms.dedup();
+
+        debug_assert!(autotopisms
+            .iter()
+            .all(|[r, c, s]| { self.permuted_rows(r).permuted_cols(c).permuted_vals(s) == *self }));
+
+        autotopisms
+    }
+
+    pub fn num_autotopisms(&self, lookup: &[Vec<(Permutation<N>, Permutation<N>)>]) -> usize {
+        self.autotopisms(lookup).len()
+    }
+
     pub fn main_class_permutation(&self) -> (Self, Permutation<3>, [Permutation<N>; 3]) {
         let mut min = *self;
         let mut permutation = (
@@ -1075,6 +1469,20 @@ impl<const N: usize> LatinSquare<N> {
         (isotopic, isotopic_permutations)
     }
 
+    /// Returns the FNV-1a hash of the rows of the main class representative.
+    /// Unlike the `HashMap` hasher this is the same across runs and machines
+    pub fn main_class_hash(&self, lookup: &[Vec<(Permutation<N>, Permutation<N>)>]) -> u64 {
+        let main_class = self.main_class_lookup(lookup);
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for val in main_class.rows.iter().flatten() {
+            hash ^= *val as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        hash
+    }
+
     pub fn main_class_lookup(&self, lookup: &[Vec<(Permutation<N>, Permutation<N>)>]) -> Self {
         let mut main_class = *self;
         let mut min_cycle_index = CYCLE_STRUCTURES[N].len() - 1;
@@ -1154,7 +1562,6 @@ impl<const N: usize> LatinSquare<N> {
             }
         }
 
-        // dbg!(self.to_string());
         // assert_eq!(self.main_class(), main_class);
 
         main_class
@@ -1185,6 +1592,53 @@ impl<const N: usize> LatinSquare<N> {
         unique
     }
 
+    /// Counts the 2x2 subsquares.
+    /// For each pair of rows, every 2-cycle of the permutation mapping one row to the other is an intercalate
+    pub fn num_intercalates(&self) -> usize {
+        assert!(N <= 16);
+
+        let mut intercalates = 0;
+
+        for [row0, row1] in TupleIterator::<2>::new(N) {
+            let rows = [self.get_row(row0), self.get_row(row1)];
+
+            let mut permutation = [0; N];
+            for i in 0..N {
+                permutation[rows[0][i] as usize] = rows[1][i] as usize;
+            }
+
+            let two_cycles: BitSet16 = (0..N)
+                .filter(|v| permutation[permutation[*v]] == *v)
+                .collect();
+
+            intercalates += two_cycles.len() / 2;
+        }
+
+        intercalates
+    }
+
+    /// Returns the squares obtained by switching the two values of one intercalate,
+    /// one for each intercalate
+    pub fn intercalate_switches(&self) -> Vec<Self> {
+        let mut switches = Vec::new();
+
+        for [row0, row1] in TupleIterator::<2>::new(N) {
+            for [col0, col1] in TupleIterator::<2>::new(N) {
+                let rows = [self.rows[row0], self.rows[row1]];
+
+                if rows[0][col0] == rows[1][col1] && rows[0][col1] == rows[1][col0] {
+                    let mut switched = self.rows;
+                    switched[row0].swap(col0, col1);
+                    switched[row1].swap(col0, col1);
+
+                    switches.push(Self::from_rows_checked(switched).unwrap());
+                }
+            }
+        }
+
+        switches
+    }
+
     fn get_subsquare(&self, rows: &[usize], cols: &[usize]) -> Vec<Vec<usize>> {
         debug_assert!(rows.len() == cols.len());
 
@@ -1271,12 +1725,7 @@ impl<const N: usize> LatinSquare<N> {
                             == BitSet16::all_less_than(k)
                 });
                 if is_subsquare {
-                    let bitset = rows
-                        .iter()
-                        .flat_map(|row| cols.iter().map(move |col| row * N + col))
-                        .collect();
-
-                    subsquares.push(bitset);
+                    subsquares.push(rows_cols_mask(&rows, &cols, N));
                 }
             }
         }
@@ -1289,15 +1738,36 @@ impl<const N: usize> LatinSquare<N> {
 
         let mut partial_sq = PartialLatinSquare::empty();
 
-        for i in mask {
-            let (i, j) = (i / N, i % N);
-
+        for (i, j) in mask.cells(N) {
             partial_sq.set(i, j, Some(self.get(i, j)));
         }
 
         partial_sq
     }
 
+    /// Returns the cells in which `self` and `other` differ
+    pub fn difference_mask(&self, other: &Self) -> BitSet128 {
+        assert!(N * N <= 128);
+
+        let mut mask = BitSet128::empty();
+
+        for i in 0..N {
+            for j in 0..N {
+                if self.get(i, j) != other.get(i, j) {
+                    mask.insert(i * N + j);
+                }
+            }
+        }
+
+        mask
+    }
+
+    /// Returns the minimal sets of cells in which `self` differs from other squares,
+    /// see `LatinSquareDyn::differences`
+    pub fn differences(&self) -> Vec<BitSet128> {
+        LatinSquareDyn::from(*self).differences()
+    }
+
     pub fn mask256(&self, mask: BitSet256) -> PartialLatinSquare<N> {
         assert!(N * N <= 256);
 
@@ -1387,6 +1857,53 @@ impl<const N: usize> LatinSquare<N> {
         cycles
     }
 
+    /// Returns the row, column and value cycle structures as a single string of the form
+    /// `R:2+2,4;C:...;V:...`. Isotopic squares have the same signature.
+    pub fn cycle_structure_signature(&self) -> String {
+        let format_cycles = |cycles: Vec<Vec<usize>>| {
+            cycles
+                .iter()
+                .map(|cycle| {
+                    cycle
+                        .iter()
+                        .map(|len| len.to_string())
+                        .collect::<Vec<_>>()
+                        .join("+")
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        format!(
+            "R:{};C:{};V:{}",
+            format_cycles(self.row_cycles()),
+            format_cycles(self.col_cycles()),
+            format_cycles(self.val_cycles())
+        )
+    }
+
+    /// Computes invariants which are equal for paratopic latin squares
+    pub fn invariants(&self) -> Invariants {
+        let mut cycle_structures = [self.row_cycles(), self.col_cycles(), self.val_cycles()];
+        cycle_structures.sort();
+
+        Invariants {
+            num_intercalates: self.num_intercalates(),
+            num_transversals: self.num_transversals(),
+            cycle_structures,
+        }
+    }
+
+    /// Computes invariants which are equal for isotopic latin squares,
+    /// unlike `invariants` these are not stable under conjugation
+    pub fn isotopy_invariants(&self) -> IsotopyInvariants {
+        IsotopyInvariants {
+            num_intercalates: self.num_intercalates(),
+            num_transversals: self.num_transversals(),
+            row_cycles: self.row_cycles(),
+        }
+    }
+
     pub fn permuted_rcs(&self, permutation: &Permutation<3>) -> Self {
         let mut rows = [[0; N]; N];
         for (i, row) in rows.iter_mut().enumerate() {
@@ -1511,6 +2028,34 @@ impl<const N: usize> LatinSquare<N> {
     }
 }
 
+/// Paratopy invariants of a latin square, see [`LatinSquare::invariants`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Invariants {
+    pub num_intercalates: usize,
+    pub num_transversals: usize,
+    /// The row, column and value cycle structures in sorted order
+    pub cycle_structures: [Vec<Vec<usize>>; 3],
+}
+
+/// Isotopy invariants of a latin square, see [`LatinSquare::isotopy_invariants`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IsotopyInvariants {
+    pub num_intercalates: usize,
+    pub num_transversals: usize,
+    /// The sorted row cycle structure
+    pub row_cycles: Vec<Vec<usize>>,
+}
+
+impl Display for IsotopyInvariants {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {:?}",
+            self.num_intercalates, self.num_transversals, self.row_cycles
+        )
+    }
+}
+
 impl<const N: usize> PartialOrd for LatinSquare<N> {
     fn partial_cmp(&self, other: &LatinSquare<N>) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -1558,6 +2103,7 @@ pub enum Error {
     InvalidLength { len: usize, expected: usize },
     InvalidChar { index: usize, char: char },
     NotALatinSquare,
+    InvalidConjugate { name: String },
 }
 
 impl Display for Error {
@@ -1570,6 +2116,12 @@ impl Display for Error {
                 write!(f, "Invalid char at index {index}: {char}")
             }
             Error::NotALatinSquare => write!(f, "The latin square property is not met"),
+            Error::InvalidConjugate { name } => {
+                write!(
+                    f,
+                    "Invalid conjugate: {name}, expected a permutation of RCS"
+                )
+            }
         }
     }
 }
@@ -1603,11 +2155,7 @@ impl<const N: usize> TryFrom<&str> for LatinSquare<N> {
 impl<const N: usize> TryFrom<[[u8; N]; N]> for LatinSquare<N> {
     type Error = Error;
     fn try_from(value: [[u8; N]; N]) -> Result<Self, Self::Error> {
-        if Self::is_valid(&value) {
-            Ok(LatinSquare::new(value))
-        } else {
-            Err(Error::NotALatinSquare)
-        }
+        Self::from_rows_checked(value)
     }
 }
 
@@ -1633,8 +2181,105 @@ impl<const N: usize> From<LatinSquare<N>> for [[u8; N]; N] {
     }
 }
 
+/// Counts the ways to complete the first rows of a reduced square.
+/// The count only depends on the values used in each column and not on the order
+/// of the columns after the first, so it is memoized by the sorted used values
+struct ReducedCompletions<const N: usize> {
+    counts: HashMap<[BitSet16; N], usize>,
+}
+
+impl<const N: usize> ReducedCompletions<N> {
+    fn new() -> Self {
+        ReducedCompletions {
+            counts: HashMap::new(),
+        }
+    }
+
+    fn add_row(used: &[BitSet16; N], row: &[u8; N]) -> [BitSet16; N] {
+        array::from_fn(|j| used[j].union(BitSet16::single(row[j].into())))
+    }
+
+    /// Returns the possible rows starting with `i` in lexicographic order
+    fn rows(i: usize, used: &[BitSet16; N]) -> Vec<[u8; N]> {
+        let mut rows = Vec::new();
+
+        let mut row = [0; N];
+        row[0] = i as u8;
+        let mut stack = vec![used[1]
+            .union(BitSet16::single(i))
+            .complement()
+            .intersect(BitSet16::all_less_than(N))];
+
+        while !stack.is_empty() {
+            let j = stack.len();
+            let candidates = &mut stack[j - 1];
+            let Some(value) = candidates.select(0) else {
+                stack.pop();
+                continue;
+            };
+            candidates.remove(value);
+            row[j] = value as u8;
+
+            if j + 1 == N {
+                rows.push(row);
+                continue;
+            }
+
+            let row_values: BitSet16 = row[..=j].iter().map(|v| *v as usize).collect();
+            stack.push(
+                used[j + 1]
+                    .union(row_values)
+                    .complement()
+                    .intersect(BitSet16::all_less_than(N)),
+            );
+        }
+
+        rows
+    }
+
+    /// Returns the number of completions given the values used in each column
+    fn count(&mut self, used: &[BitSet16; N]) -> usize {
+        let i = used[0].len();
+        if i == N {
+            return 1;
+        }
+
+        let mut key = *used;
+        key[1..].sort();
+
+        if let Some(count) = self.counts.get(&key) {
+            return *count;
+        }
+
+        let count = Self::rows(i, used)
+            .iter()
+            .map(|row| self.count(&Self::add_row(used, row)))
+            .sum();
+        self.counts.insert(key, count);
+
+        count
+    }
+}
+
 pub fn n_disjoint_transversals_bitset<const N: usize>(
     transversals: &[BitSet128],
+) -> Vec<[BitSet128; N]> {
+    disjoint_transversals_bitset_up_to(transversals, usize::MAX)
+}
+
+/// Returns the first set of `N` disjoint transversals found by `n_disjoint_transversals_bitset`
+fn first_disjoint_transversals_bitset<const N: usize>(
+    transversals: &[BitSet128],
+) -> Option<[BitSet128; N]> {
+    disjoint_transversals_bitset_up_to(transversals, 1)
+        .into_iter()
+        .next()
+}
+
+/// Same as `n_disjoint_transversals_bitset`, but stops after `max` sets are found
+fn disjoint_transversals_bitset_up_to<const N: usize>(
+    transversals: &[BitSet128],
+    max: usize,
 ) -> Vec<[BitSet128; N]> {
     let mut transversals_by_start: [[Vec<_>; N]; N] =
         array::from_fn(|_| array::from_fn(|_| Vec::new()));
@@ -1683,8 +2328,14 @@ pub fn n_disjoint_transversals_bitset<const N: usize>(
                     if transversals_by_start[N - 1][second_row].contains(&left) {
                         disjoint[N - 1] = left;
                         disjoint_transversals.push(disjoint);
+                        if disjoint_transversals.len() >= max {
+                            return disjoint_transversals;
+                        }
                         if disjoint_transversals.len() % 1000 == 0 {
-                            dbg!(disjoint_transversals.len());
+                            progress(format_args!(
+                                "n = {N}: {} disjoint transversal sets",
+                                disjoint_transversals.len()
+                            ));
                         }
                     }
                 } else {
@@ -1724,10 +2375,629 @@ pub fn n_disjoint_transversals_bitset<const N: usize>(
 #[cfg(test)]
 mod test {
 
-    use crate::cycles::generate_minimize_rows_lookup;
+    use crate::{
+        cycles::generate_minimize_rows_lookup, latin_square_generator::LatinSquareGeneratorDyn,
+        random_latin_square_generator::RandomLatinSquareGeneratorDyn,
+    };
 
     use super::*;
 
+    fn random_sqs<const N: usize>(seed: u64, count: usize) -> Vec<LatinSquare<N>> {
+        RandomLatinSquareGeneratorDyn::new(N, seed)
+            .take(count)
+            .map(|sq| (&sq).try_into().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn transpose() {
+        let sq = LatinSquare::new([[0, 1, 2, 3], [1, 3, 0, 2], [2, 0, 3, 1], [3, 2, 1, 0]]);
+
+        assert_eq!(
+            sq.transpose(),
+            sq.permuted_rcs(&Permutation::from_array([1, 0, 2]))
+        );
+        assert_eq!(sq.transpose().transpose(), sq);
+    }
+
+    #[test]
+    fn conjugate() {
+        let sq = LatinSquare::new([[0, 1, 2, 3], [1, 3, 0, 2], [2, 0, 3, 1], [3, 2, 1, 0]]);
+
+        assert_eq!(sq.conjugate("CRS").unwrap(), sq.transpose());
+        assert_eq!(sq.conjugate("RCS").unwrap(), sq);
+        assert_eq!(sq.conjugate("crs").unwrap(), sq.transpose());
+
+        for (rcs, conjugate) in PermutationIter::<3>::new().zip(sq.conjugates()) {
+            let name: String = rcs.apply_array(['R', 'C', 'S']).into_iter().collect();
+            assert_eq!(sq.conjugate(&name).unwrap(), conjugate);
+        }
+
+        assert!(sq.conjugate("RRS").is_err());
+        assert!(sq.conjugate("RC").is_err());
+    }
+
+    #[test]
+    fn is_symmetric() {
+        let cyclic = LatinSquare::new([[0, 1, 2], [1, 2, 0], [2, 0, 1]]);
+        assert!(cyclic.is_symmetric());
+
+        let asymmetric = LatinSquare::new([[0, 1, 2], [2, 0, 1], [1, 2, 0]]);
+        assert!(!asymmetric.is_symmetric());
+    }
+
+    #[test]
+    fn is_group() {
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+        assert!(z5.is_associative());
+        assert_eq!(z5.group_identity(), Some(0));
+
+        let loop5 = LatinSquare::new([
+            [0, 1, 2, 3, 4],
+            [1, 0, 3, 4, 2],
+            [2, 4, 0, 1, 3],
+            [3, 2, 4, 0, 1],
+            [4, 3, 1, 2, 0],
+        ]);
+        assert!(!loop5.is_associative());
+        assert_eq!(loop5.group_identity(), Some(0));
+    }
+
+    #[test]
+    fn translations() {
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+
+        for a in 0..5 {
+            let left = z5.left_translation(a);
+            let right = z5.right_translation(a);
+            assert_eq!(left.compose(&left.inverse()), Permutation::identity());
+            assert_eq!(right.compose(&right.inverse()), Permutation::identity());
+
+            for b in 0..5 {
+                assert_eq!(
+                    left.compose(&z5.left_translation(b)),
+                    z5.left_translation(z5.op(a, b))
+                );
+                assert_eq!(
+                    z5.right_translation(b).compose(&right),
+                    z5.right_translation(z5.op(a, b))
+                );
+            }
+        }
+
+        let mut lefts: Vec<_> = (0..5).map(|a| z5.left_translation(a)).collect();
+        lefts.sort();
+        lefts.dedup();
+        assert_eq!(lefts.len(), 5);
+
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        for a in 0..4 {
+            assert_eq!(
+                klein
+                    .left_translation(a)
+                    .compose(&klein.left_translation(a)),
+                Permutation::identity()
+            );
+        }
+    }
+
+    #[test]
+    fn value_permutations() {
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+        let perms = z5.value_permutations();
+
+        for (v, perm) in perms.iter().enumerate() {
+            for row in 0..5 {
+                assert_eq!(z5.get(row, perm.apply(row)), v);
+            }
+        }
+
+        // in a group, the permutation taking P_v to P_u is the left translation by u * v^-1
+        for u in 0..5 {
+            for v in 0..5 {
+                assert_eq!(
+                    perms[u].inverse().compose(&perms[v]),
+                    z5.left_translation((u + 5 - v) % 5)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn main_class_hash() {
+        let lookup = generate_minimize_rows_lookup();
+
+        let z4 = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let isotopic = z4
+            .permuted_rows(&Permutation::from_array([2, 0, 3, 1]))
+            .permuted_cols(&Permutation::from_array([1, 3, 0, 2]));
+
+        assert_eq!(
+            z4.main_class_hash(&lookup),
+            isotopic.main_class_hash(&lookup)
+        );
+        assert_ne!(z4.main_class_hash(&lookup), klein.main_class_hash(&lookup));
+        assert_eq!(
+            z4.transpose().main_class_hash(&lookup),
+            z4.main_class_hash(&lookup)
+        );
+    }
+
+    #[test]
+    fn max_disjoint_transversal_cover() {
+        let z4 = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+
+        assert!(z4.max_disjoint_transversal_cover().is_empty());
+        assert_eq!(z5.max_disjoint_transversal_cover().len(), 5);
+
+        for sq in random_sqs::<6>(16, 5) {
+            let cover = sq.max_disjoint_transversal_cover();
+            assert_eq!(cover.len(), sq.max_disjoint_transversals());
+
+            let transversals = sq.transversals_bitset();
+            for (i, a) in cover.iter().enumerate() {
+                assert!(transversals.contains(a));
+                for b in &cover[i + 1..] {
+                    assert!(a.is_disjoint(*b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mols_with_deadline() {
+        let lookup = generate_minimize_rows_lookup();
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+
+        let all = klein.mols(&lookup);
+        assert!(!all.is_empty());
+
+        let timed_out = klein.mols_with_deadline(&lookup, Some(Instant::now()));
+        assert!(timed_out.is_empty());
+
+        let prefix = klein.mols_with_deadline(
+            &lookup,
+            Some(Instant::now() + std::time::Duration::from_millis(1)),
+        );
+        assert_eq!(prefix, all[..prefix.len()]);
+    }
+
+    #[test]
+    fn intercalate_switches() {
+        let z4 = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+
+        assert!(z5.intercalate_switches().is_empty());
+
+        let switches = z4.intercalate_switches();
+        assert_eq!(switches.len(), z4.num_intercalates());
+        for sq in switches {
+            assert!(LatinSquare::is_valid(sq.values()));
+            assert_eq!(sq.hamming_distance(&z4), 4);
+        }
+
+        for sq in random_sqs::<6>(26, 5) {
+            let switches = sq.intercalate_switches();
+            assert_eq!(switches.len(), sq.num_intercalates());
+            for switched in switches {
+                assert_eq!(switched.hamming_distance(&sq), 4);
+            }
+        }
+    }
+
+    #[test]
+    fn num_autotopisms() {
+        // the autotopism group of a group table has order |G|^2 * |Aut(G)|
+        let z4 = LatinSquare::<4>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 4) as u8)));
+        assert_eq!(z4.num_autotopisms(&generate_minimize_rows_lookup()), 16 * 2);
+
+        let klein = LatinSquare::<4>::new(array::from_fn(|i| array::from_fn(|j| (i ^ j) as u8)));
+        assert_eq!(
+            klein.num_autotopisms(&generate_minimize_rows_lookup()),
+            16 * 6
+        );
+
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+        assert_eq!(z5.num_autotopisms(&generate_minimize_rows_lookup()), 25 * 4);
+
+        for sq in random_sqs::<6>(16, 5) {
+            let lookup = generate_minimize_rows_lookup();
+            let count = sq.num_autotopisms(&lookup);
+            assert_eq!(count, sq.isotopy_class().num_autotopisms(&lookup));
+        }
+    }
+
+    #[test]
+    fn num_intercalates() {
+        for sq in random_sqs::<6>(6, 20) {
+            assert_eq!(sq.num_intercalates(), sq.num_subsquares(2));
+        }
+        for sq in random_sqs::<7>(7, 20) {
+            assert_eq!(sq.num_intercalates(), sq.num_subsquares(2));
+        }
+        for sq in random_sqs::<8>(8, 20) {
+            assert_eq!(sq.num_intercalates(), sq.num_subsquares(2));
+        }
+    }
+
+    #[test]
+    fn transversals_bitset256() {
+        let cyclic =
+            LatinSquare::<7>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 7) as u8)));
+        assert_eq!(cyclic.transversals_bitset256().len(), 133);
+
+        for sq in random_sqs::<7>(7, 5).into_iter().chain([cyclic]) {
+            let transversals: Vec<Vec<usize>> = sq
+                .transversals_bitset()
+                .into_iter()
+                .map(|t| t.into_iter().collect())
+                .collect();
+            let transversals256: Vec<Vec<usize>> = sq
+                .transversals_bitset256()
+                .into_iter()
+                .map(|t| t.into_iter().collect())
+                .collect();
+
+            assert_eq!(transversals, transversals256);
+        }
+    }
+
+    #[test]
+    fn num_transversals_cached() {
+        let mut cache = HashMap::new();
+
+        let sqs: Vec<_> = LatinSquareGeneratorDyn::new(5)
+            .step_by(97)
+            .map(|sq| LatinSquare::<5>::try_from(&sq).unwrap())
+            .collect();
+
+        for sq in sqs.iter().chain(sqs.iter()) {
+            assert_eq!(
+                sq.num_transversals_cached(&mut cache),
+                sq.num_transversals()
+            );
+        }
+        assert_eq!(cache.len(), sqs.len());
+    }
+
+    #[test]
+    fn num_transversals_threaded() {
+        for sq in random_sqs::<7>(17, 5) {
+            let count = sq.num_transversals();
+            assert_eq!(sq.num_transversals_threaded(1), count);
+            assert_eq!(sq.num_transversals_threaded(3), count);
+            assert_eq!(sq.num_transversals_threaded(16), count);
+        }
+    }
+
+    #[test]
+    fn greedy_mols() {
+        let z7 = LatinSquare::<7>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 7) as u8)));
+
+        for sq in random_sqs::<7>(7, 3).into_iter().chain([z7]) {
+            let mols = sq.greedy_mols();
+            assert_eq!(mols[0], sq);
+
+            for (i, a) in mols.iter().enumerate() {
+                for b in &mols[i + 1..] {
+                    assert!(a.is_orthogonal_to(b));
+                }
+            }
+        }
+
+        assert!(z7.greedy_mols().len() >= 2);
+    }
+
+    #[test]
+    fn extend_mols() {
+        let a = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let b = LatinSquare::new([[0, 1, 2, 3], [2, 3, 0, 1], [3, 2, 1, 0], [1, 0, 3, 2]]);
+
+        let extensions = LatinSquare::extend_mols(&[a, b], 3);
+        assert!(!extensions.is_empty());
+
+        for mols in &extensions {
+            assert_eq!(mols.len(), 3);
+            assert_eq!(&mols.squares()[..2], &[a, b]);
+            assert!(Mols::new(mols.squares().to_vec()).is_ok());
+        }
+
+        assert!(LatinSquare::extend_mols(&[a, b], 4).is_empty());
+        assert_eq!(LatinSquare::extend_mols(&[a, b], 2).len(), 1);
+    }
+
+    #[test]
+    fn isotopy_class_if_not_reduced() {
+        let lookup = generate_minimize_rows_lookup();
+
+        for sq in random_sqs::<6>(36, 5) {
+            let isotopy_class = sq.isotopy_class_lookup(&lookup);
+            assert_eq!(isotopy_class.isotopy_class_if_not_reduced(&lookup), None);
+
+            let permuted =
+                isotopy_class.permuted_rows(&Permutation::from_array([1, 0, 2, 3, 4, 5]));
+            assert_eq!(
+                permuted.isotopy_class_if_not_reduced(&lookup),
+                Some(isotopy_class)
+            );
+        }
+    }
+
+    #[test]
+    fn isotopy_class_permutations_if_not_reduced() {
+        let lookup = generate_minimize_rows_lookup();
+
+        for sq in random_sqs::<6>(36, 5) {
+            let isotopy_class = sq.isotopy_class_lookup(&lookup);
+            assert!(isotopy_class
+                .isotopy_class_permutations_if_not_reduced(&lookup)
+                .is_none());
+
+            let permuted =
+                isotopy_class.permuted_rows(&Permutation::from_array([1, 0, 2, 3, 4, 5]));
+            let (class, permutations) = permuted
+                .isotopy_class_permutations_if_not_reduced(&lookup)
+                .unwrap();
+            assert_eq!(class, isotopy_class);
+            let [rows, cols, vals] = &permutations[0];
+            assert_eq!(
+                permuted
+                    .permuted_rows(rows)
+                    .permuted_cols(cols)
+                    .permuted_vals(vals),
+                isotopy_class
+            );
+        }
+    }
+
+    #[test]
+    fn from_permutations() {
+        let perms = [[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]];
+        let sq = LatinSquare::from_permutations(perms.map(Permutation::from_array)).unwrap();
+        assert_eq!(sq.to_values(), perms.map(|row| row.map(|val| val as u8)));
+
+        let perms = [[0, 1, 2, 3], [1, 0, 3, 2], [1, 0, 3, 2], [3, 2, 1, 0]];
+        assert!(matches!(
+            LatinSquare::from_permutations(perms.map(Permutation::from_array)),
+            Err(Error::NotALatinSquare)
+        ));
+        assert!(matches!(
+            LatinSquare::from_rows_checked(perms.map(|row| row.map(|val| val as u8))),
+            Err(Error::NotALatinSquare)
+        ));
+    }
+
+    #[test]
+    fn cycle_structure_signature() {
+        let z4 = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+
+        let isotopic = z4
+            .permuted_rows(&Permutation::from_array([2, 0, 3, 1]))
+            .permuted_cols(&Permutation::from_array([1, 3, 0, 2]))
+            .permuted_vals(&Permutation::from_array([3, 1, 2, 0]));
+
+        assert_eq!(
+            z4.cycle_structure_signature(),
+            isotopic.cycle_structure_signature()
+        );
+        assert_ne!(
+            z4.cycle_structure_signature(),
+            klein.cycle_structure_signature()
+        );
+        assert_eq!(
+            klein.cycle_structure_signature(),
+            "R:2+2,2+2,2+2,2+2,2+2,2+2;C:2+2,2+2,2+2,2+2,2+2,2+2;V:2+2,2+2,2+2,2+2,2+2,2+2"
+        );
+    }
+
+    #[test]
+    fn invariants() {
+        let sq = LatinSquare::new([
+            [0, 1, 2, 3, 4],
+            [1, 0, 3, 4, 2],
+            [2, 3, 4, 0, 1],
+            [3, 4, 1, 2, 0],
+            [4, 2, 0, 1, 3],
+        ]);
+        let cyclic = LatinSquare::new([
+            [0, 1, 2, 3, 4],
+            [1, 2, 3, 4, 0],
+            [2, 3, 4, 0, 1],
+            [3, 4, 0, 1, 2],
+            [4, 0, 1, 2, 3],
+        ]);
+
+        let invariants = sq.invariants();
+        assert_eq!(sq.conjugates().count(), 6);
+        for conjugate in sq.conjugates() {
+            assert_eq!(conjugate.invariants(), invariants);
+        }
+
+        let isotopic = sq
+            .permuted_rows(&Permutation::from_array([2, 0, 3, 1, 4]))
+            .permuted_cols(&Permutation::from_array([1, 3, 0, 4, 2]))
+            .permuted_vals(&Permutation::from_array([4, 1, 2, 0, 3]));
+        assert_eq!(isotopic.invariants(), invariants);
+
+        assert_ne!(cyclic.invariants(), invariants);
+    }
+
+    #[test]
+    fn isotopy_invariants() {
+        let sq = LatinSquare::new([
+            [0, 1, 2, 3, 4],
+            [1, 0, 3, 4, 2],
+            [2, 3, 4, 0, 1],
+            [3, 4, 1, 2, 0],
+            [4, 2, 0, 1, 3],
+        ]);
+
+        let isotopic = sq
+            .permuted_rows(&Permutation::from_array([3, 0, 4, 1, 2]))
+            .permuted_cols(&Permutation::from_array([2, 4, 1, 0, 3]))
+            .permuted_vals(&Permutation::from_array([1, 2, 0, 4, 3]));
+        assert_ne!(sq, isotopic);
+        assert_eq!(sq.isotopy_invariants(), isotopic.isotopy_invariants());
+
+        let invariants = sq.isotopy_invariants();
+        assert_eq!(invariants.num_intercalates, sq.num_intercalates());
+        assert_eq!(invariants.num_transversals, sq.num_transversals());
+        assert_eq!(invariants.row_cycles, sq.row_cycles());
+    }
+
+    #[test]
+    fn from_complete_mapping() {
+        let cyclic =
+            LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+
+        let mut count = 0;
+        for perm in PermutationIter::<5>::new() {
+            if let Some(sq) = LatinSquare::from_complete_mapping(&perm) {
+                assert!(sq.is_orthogonal_to(&cyclic));
+                count += 1;
+            }
+        }
+        assert_eq!(count, 15);
+
+        assert_eq!(
+            LatinSquare::<5>::from_complete_mapping(&Permutation::identity()),
+            None
+        );
+        assert!(PermutationIter::<4>::new()
+            .all(|perm| { LatinSquare::<4>::from_complete_mapping(&perm).is_none() }));
+    }
+
+    #[test]
+    fn reduced_with_perms() {
+        for sq in RandomLatinSquareGeneratorDyn::new(6, 6).take(20) {
+            let sq = LatinSquare::<6>::try_from(&sq).unwrap();
+            let (reduced, [rows, cols, vals]) = sq.reduced_with_perms();
+
+            assert!(reduced.is_reduced());
+            assert_eq!(reduced, sq.reduced());
+            assert_eq!(
+                sq.permuted_rows(&rows)
+                    .permuted_cols(&cols)
+                    .permuted_vals(&vals),
+                reduced
+            );
+        }
+    }
+
+    #[test]
+    fn is_diagonal() {
+        let diagonal = LatinSquare::new([[0, 1, 2, 3], [2, 3, 0, 1], [3, 2, 1, 0], [1, 0, 3, 2]]);
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+
+        assert!(diagonal.is_diagonal());
+        assert!(!klein.is_diagonal());
+        assert!(!klein.transpose().is_diagonal());
+    }
+
+    #[test]
+    fn hamming_distance() {
+        let sq = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let swapped = LatinSquare::new([[1, 0, 2, 3], [0, 1, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+
+        assert_eq!(sq.hamming_distance(&sq), 0);
+        assert_eq!(sq.hamming_distance(&swapped), 4);
+        assert_eq!(swapped.hamming_distance(&sq), 4);
+        assert_eq!(sq.num_same_rows(&swapped), 0);
+        assert_eq!(sq.num_same_rows(&sq), 4);
+
+        let sq = LatinSquareDyn::from(sq);
+        let swapped = LatinSquareDyn::from(swapped);
+        assert_eq!(sq.hamming_distance(&sq), 0);
+        assert_eq!(sq.hamming_distance(&swapped), 4);
+    }
+
+    #[test]
+    fn differences() {
+        for sq in random_sqs::<5>(55, 3) {
+            assert!(!sq.differences().is_empty());
+        }
+
+        let a = LatinSquare::new([[0, 1, 2], [1, 2, 0], [2, 0, 1]]);
+        let b = LatinSquare::new([[0, 1, 2], [2, 0, 1], [1, 2, 0]]);
+        assert_eq!(a.difference_mask(&a), BitSet128::empty());
+        assert_eq!(a.difference_mask(&b), BitSet128::from_range(3..9));
+    }
+
+    #[test]
+    fn reduced_index() {
+        let reduced: Vec<_> = LatinSquare::<4>::enumerate_reduced().collect();
+        assert_eq!(reduced.len(), 4);
+        assert!(reduced.is_sorted());
+
+        for (index, sq) in reduced.iter().enumerate() {
+            assert!(sq.is_reduced());
+            assert_eq!(sq.reduced_index(), Some(index));
+            assert_eq!(LatinSquare::from_reduced_index(index), Some(*sq));
+        }
+        assert_eq!(LatinSquare::<4>::from_reduced_index(4), None);
+
+        let not_reduced = LatinSquare::new([[1, 0, 2], [0, 2, 1], [2, 1, 0]]);
+        assert_eq!(not_reduced.reduced_index(), None);
+
+        assert_eq!(LatinSquare::<5>::enumerate_reduced().count(), 56);
+
+        let reduced: Vec<_> = LatinSquare::<6>::enumerate_reduced().collect();
+        assert_eq!(reduced.len(), 9408);
+        assert!(reduced.is_sorted());
+        for (index, sq) in reduced.iter().enumerate().step_by(97) {
+            assert_eq!(sq.reduced_index(), Some(index));
+            assert_eq!(LatinSquare::from_reduced_index(index), Some(*sq));
+        }
+        assert_eq!(LatinSquare::<6>::from_reduced_index(9408), None);
+
+        let last = LatinSquare::<1>::from_reduced_index(0).unwrap();
+        assert_eq!(last.reduced_index(), Some(0));
+    }
+
+    #[test]
+    fn reduced() {
+        for sq in random_sqs::<7>(70, 20) {
+            let reduced = sq.reduced();
+            assert!(reduced.is_reduced());
+            assert_eq!(reduced.reduced(), reduced);
+            assert_eq!(reduced.isotopy_class(), sq.isotopy_class());
+        }
+    }
+
+    #[test]
+    fn has_orthogonal_mate() {
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+        assert!(z5.has_orthogonal_mate());
+
+        let z4 = LatinSquare::<4>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 4) as u8)));
+        assert!(!z4.has_orthogonal_mate());
+
+        for sq in random_sqs::<6>(66, 5) {
+            assert!(!sq.has_orthogonal_mate());
+        }
+        for sq in random_sqs::<5>(55, 5) {
+            assert_eq!(sq.has_orthogonal_mate(), sq.num_orthogonal_mates() > 0);
+        }
+    }
+
+    #[test]
+    fn num_orthogonal_mates() {
+        let z5 = LatinSquare::<5>::new(array::from_fn(|i| array::from_fn(|j| ((i + j) % 5) as u8)));
+        assert!(z5.num_orthogonal_mates() > 0);
+        assert_eq!(z5.num_orthogonal_mates(), z5.orthogonal_squares().count());
+
+        for sq in random_sqs::<5>(5, 5) {
+            assert_eq!(sq.num_orthogonal_mates(), sq.orthogonal_squares().count());
+        }
+        for sq in random_sqs::<6>(6, 5) {
+            assert_eq!(sq.num_orthogonal_mates(), 0);
+            assert_eq!(sq.num_orthogonal_mates(), sq.orthogonal_squares().count());
+        }
+    }
+
     #[test]
     fn normalize_main_class() {
         let lookup = generate_minimize_rows_lookup();
@@ -1,11 +1,13 @@
 use std::fmt::{Display, Write};
 
 use crate::{
-    bitset::{BitSet128, BitSet16},
+    bitset::{rows_cols_mask, BitSet128, BitSet16},
     latin_square::LatinSquare,
     latin_square_generator::LatinSquareGeneratorDyn,
     partial_latin_square_dyn::PartialLatinSquareDyn,
+    permutation::{Permutation, PermutationIter},
     permutation_dyn::PermutationDyn,
+    random_latin_square_generator::RandomLatinSquareGeneratorDyn,
     tuple_iterator::{TupleIterator, TupleIteratorDyn},
 };
 
@@ -38,6 +40,224 @@ impl LatinSquareDyn {
         &self.values
     }
 
+    /// Builds a square from its rows, which all need to have length `rows.len()`
+    pub fn try_from_rows(rows: Vec<Vec<u8>>) -> Result<Self, Error> {
+        let n = rows.len();
+        if n > 16 {
+            return Err(Error::InvalidOrder { n });
+        }
+
+        let mut values = Vec::with_capacity(n * n);
+        for (i, row) in rows.into_iter().enumerate() {
+            if row.len() != n {
+                return Err(Error::InvalidRowLength {
+                    row: i,
+                    len: row.len(),
+                    expected: n,
+                });
+            }
+
+            for (j, entry) in row.into_iter().enumerate() {
+                if entry as usize >= n {
+                    return Err(Error::InvalidEntry {
+                        row: i,
+                        col: j,
+                        entry: entry.into(),
+                    });
+                }
+                values.push(entry);
+            }
+        }
+
+        LatinSquareDyn::from_boxed_slice(values.into_boxed_slice()).ok_or(Error::InvalidLatinSquare)
+    }
+
+    /// Parses a square written as a JSON array of rows, like `[[0,1],[1,0]]`.
+    /// Numbers which don't fit into a `u8` are reported as invalid JSON
+    pub fn from_json(value: &str) -> Result<Self, Error> {
+        let mut chars = value
+            .char_indices()
+            .filter(|(_, c)| !c.is_whitespace())
+            .peekable();
+
+        let expect = |expected: char, chars: &mut std::iter::Peekable<_>| match chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((index, _)) => Err(Error::InvalidJson { index }),
+            None => Err(Error::InvalidJson { index: value.len() }),
+        };
+
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+
+        expect('[', &mut chars)?;
+        while chars.peek().is_some_and(|(_, c)| *c == '[') {
+            expect('[', &mut chars)?;
+
+            let mut row = Vec::new();
+            while let Some((index, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                let mut entry = c.to_digit(10).unwrap() as u8;
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    entry = entry
+                        .checked_mul(10)
+                        .and_then(|entry| entry.checked_add(c.to_digit(10).unwrap() as u8))
+                        .ok_or(Error::InvalidJson { index })?;
+                }
+                row.push(entry);
+
+                match chars.next_if(|(_, c)| *c == ',') {
+                    None => break,
+                    Some((index, _)) => {
+                        if !chars.peek().is_some_and(|(_, c)| c.is_ascii_digit()) {
+                            return Err(Error::InvalidJson { index });
+                        }
+                    }
+                }
+            }
+            expect(']', &mut chars)?;
+
+            rows.push(row);
+
+            match chars.next_if(|(_, c)| *c == ',') {
+                None => break,
+                Some((index, _)) => {
+                    if !chars.peek().is_some_and(|(_, c)| *c == '[') {
+                        return Err(Error::InvalidJson { index });
+                    }
+                }
+            }
+        }
+        expect(']', &mut chars)?;
+
+        if let Some((index, _)) = chars.next() {
+            return Err(Error::InvalidJson { index });
+        }
+
+        Self::try_from_rows(rows)
+    }
+
+    /// Returns the rows on separate lines with space separated, zero-padded decimal values.
+    /// Unlike `Display` this works for any order
+    pub fn to_spaced_string(&self) -> String {
+        let n = self.n;
+        let width = n.saturating_sub(1).to_string().len();
+
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| format!("{:0width$}", self.get(i, j)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_json(&self) -> String {
+        let rows: Vec<_> = (0..self.n)
+            .map(|i| {
+                let row: Vec<_> = self.get_row(i).iter().map(|v| v.to_string()).collect();
+                format!("[{}]", row.join(","))
+            })
+            .collect();
+
+        format!("[{}]", rows.join(","))
+    }
+
+    /// Permutes the roles of rows, columns and symbols, see `LatinSquare::permuted_rcs`
+    pub fn permuted_rcs(&self, permutation: &Permutation<3>) -> Self {
+        let n = self.n;
+        let mut values = vec![0; n * n].into_boxed_slice();
+
+        for i in 0..n {
+            for j in 0..n {
+                let [row, col, val] = permutation.apply_array([i, j, self.get(i, j)]);
+                values[row * n + col] = val as u8;
+            }
+        }
+
+        LatinSquareDyn { n, values }
+    }
+
+    pub fn conjugates(&self) -> impl Iterator<Item = Self> + '_ {
+        PermutationIter::new().map(|perm| self.permuted_rcs(&perm))
+    }
+
+    pub fn get_row(&self, row: usize) -> &[u8] {
+        &self.values[row * self.n..(row + 1) * self.n]
+    }
+
+    pub fn get_col(&self, col: usize) -> Vec<u8> {
+        (0..self.n)
+            .map(|row| self.values[row * self.n + col])
+            .collect()
+    }
+
+    /// Returns for each row, in which column the value `val` appears
+    pub fn get_val(&self, val: usize) -> Vec<u8> {
+        (0..self.n)
+            .map(|row| {
+                self.get_row(row)
+                    .iter()
+                    .position(|v| *v as usize == val)
+                    .unwrap() as u8
+            })
+            .collect()
+    }
+
+    pub fn row_cycles(&self) -> Vec<Vec<usize>> {
+        let rows: Vec<_> = (0..self.n).map(|i| self.get_row(i).to_vec()).collect();
+        Self::cycles(&rows)
+    }
+
+    pub fn col_cycles(&self) -> Vec<Vec<usize>> {
+        let cols: Vec<_> = (0..self.n).map(|i| self.get_col(i)).collect();
+        Self::cycles(&cols)
+    }
+
+    pub fn val_cycles(&self) -> Vec<Vec<usize>> {
+        let vals: Vec<_> = (0..self.n).map(|i| self.get_val(i)).collect();
+        Self::cycles(&vals)
+    }
+
+    /// Returns the sorted cycle lengths of the permutations mapping one line to another,
+    /// for each pair of lines
+    fn cycles(lines: &[Vec<u8>]) -> Vec<Vec<usize>> {
+        let n = lines.len();
+        let mut cycles = Vec::new();
+
+        for pair in TupleIteratorDyn::new(n, 2) {
+            let [a, b] = [&lines[pair[0]], &lines[pair[1]]];
+
+            let mut permutation = vec![0; n];
+            for i in 0..n {
+                permutation[a[i] as usize] = b[i] as usize;
+            }
+
+            let mut cycle = PermutationDyn::from_vec(permutation).cycle_lengths();
+            cycle.sort();
+
+            cycles.push(cycle);
+        }
+
+        cycles.sort();
+        cycles
+    }
+
+    /// Checks that the first row and the first column are `0, 1, ..., n-1`
+    pub fn is_reduced(&self) -> bool {
+        (0..self.n).all(|i| self.get(0, i) == i && self.get(i, 0) == i)
+    }
+
+    /// Returns the number of leading rows that are equal in both squares
+    pub fn num_same_rows(&self, other: &Self) -> usize {
+        if self.n != other.n {
+            return 0;
+        }
+
+        (0..self.n)
+            .find(|i| self.get_row(*i) != other.get_row(*i))
+            .unwrap_or(self.n)
+    }
+
     fn is_valid(values: &[u8]) -> bool {
         let Some(n) = isqrt(values.len()) else {
             return false;
@@ -55,6 +275,81 @@ impl LatinSquareDyn {
         })
     }
 
+    /// Checks the latin square property and returns all violations of it.
+    /// If the length of `values` is not the square of an order up to 16, only that is reported
+    pub fn validation_report(values: &[u8]) -> Result<(), Vec<Conflict>> {
+        let len = values.len();
+        let Some(n) = isqrt(len).filter(|n| *n <= 16) else {
+            return Err(vec![Conflict::InvalidLength { len }]);
+        };
+
+        let mut conflicts = Vec::new();
+
+        for (index, value) in values.iter().enumerate() {
+            if *value as usize >= n {
+                conflicts.push(Conflict::InvalidSymbol {
+                    row: index / n,
+                    col: index % n,
+                    symbol: *value as usize,
+                });
+            }
+        }
+
+        // each symbol is only reported once per line
+        let duplicates = |line: &mut dyn Iterator<Item = usize>| {
+            let mut seen = BitSet16::empty();
+            let mut duplicates = BitSet16::empty();
+            for value in line.filter(|value| *value < n) {
+                if seen.contains(value) {
+                    duplicates.insert(value);
+                }
+                seen.insert(value);
+            }
+            duplicates
+        };
+
+        for row in 0..n {
+            for symbol in duplicates(&mut (0..n).map(|col| values[row * n + col] as usize)) {
+                conflicts.push(Conflict::DuplicateInRow { row, symbol });
+            }
+        }
+        for col in 0..n {
+            for symbol in duplicates(&mut (0..n).map(|row| values[row * n + col] as usize)) {
+                conflicts.push(Conflict::DuplicateInCol { col, symbol });
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    pub fn is_orthogonal_to(&self, other: &Self) -> bool {
+        assert_eq!(self.n, other.n);
+        let n = self.n;
+        assert!(n <= 16);
+
+        for value in 0..n {
+            let mut other_values = BitSet16::empty();
+
+            for i in 0..n {
+                for j in 0..n {
+                    if self.get(i, j) == value {
+                        other_values.insert(other.get(i, j));
+                    }
+                }
+            }
+
+            if other_values != BitSet16::all_less_than(n) {
+                return false;
+            }
+        }
+
+        true
+    }
+
     pub fn differences(&self) -> Vec<BitSet128> {
         let mut sets: Vec<BitSet128> = Vec::new();
 
@@ -136,21 +431,54 @@ impl LatinSquareDyn {
         mask
     }
 
+    /// Returns the number of cells in which `self` and `other` differ
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        assert_eq!(self.n, other.n);
+
+        self.values
+            .iter()
+            .zip(other.values.iter())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+
     pub fn mask(&self, mask: BitSet128) -> PartialLatinSquareDyn {
         let mut partial_sq = PartialLatinSquareDyn::empty(self.n);
 
         assert!(self.n * self.n <= 128);
 
-        for index in mask {
-            let i = index / self.n;
-            let j = index % self.n;
-
+        for (i, j) in mask.cells(self.n) {
             partial_sq.set(i, j, Some(self.get(i, j)));
         }
 
         partial_sq
     }
 
+    /// Removes entries in a random order, as long as the partial square stays uniquely completable to `self`
+    pub fn random_uniquely_completable_partial(&self, seed: u64) -> PartialLatinSquareDyn {
+        let n = self.n;
+        let mut random_state = [seed, 1, 2, 3];
+
+        let mut cells: Vec<_> = (0..n * n).collect();
+        for i in (1..cells.len()).rev() {
+            let (random, next_state) = RandomLatinSquareGeneratorDyn::xoshiro(random_state);
+            random_state = next_state;
+            cells.swap(i, random as usize % (i + 1));
+        }
+
+        let mut partial_sq = PartialLatinSquareDyn::from(self);
+        for cell in cells {
+            let (i, j) = (cell / n, cell % n);
+
+            partial_sq.set(i, j, None);
+            if !partial_sq.is_uniquely_completable_to(self) {
+                partial_sq.set(i, j, Some(self.get(i, j)));
+            }
+        }
+
+        partial_sq
+    }
+
     pub fn get_subsquare_dyn(&self, rows: &[usize], cols: &[usize]) -> Vec<Vec<usize>> {
         debug_assert!(rows.len() == cols.len());
 
@@ -172,6 +500,51 @@ impl LatinSquareDyn {
         let n = self.n;
         assert!(n < 16);
 
+        let mut rows = vec![0; k];
+        let mut cols = vec![0; k];
+
+        let mut row_tuples = TupleIteratorDyn::new(n, k);
+        while row_tuples.next_into(&mut rows) {
+            let mut col_tuples = TupleIteratorDyn::new(n, k);
+            while col_tuples.next_into(&mut cols) {
+                let mut subsquare = self.get_subsquare_dyn(&rows, &cols);
+
+                let mut permutation: Vec<_> = subsquare[0].to_vec();
+
+                for i in 0..n {
+                    if !permutation.contains(&i) {
+                        permutation.push(i);
+                    }
+                }
+
+                let permutation = PermutationDyn::from_vec(permutation).inverse();
+
+                for row in subsquare.iter_mut() {
+                    for val in row.iter_mut() {
+                        *val = permutation.apply(*val);
+                    }
+                }
+
+                let is_subsquare = (0..k).all(|i| {
+                    (0..k).map(|j| subsquare[i][j]).collect::<BitSet16>()
+                        == BitSet16::all_less_than(k)
+                        && (0..k).map(|j| subsquare[j][i]).collect::<BitSet16>()
+                            == BitSet16::all_less_than(k)
+                });
+                if is_subsquare {
+                    subsquares += 1;
+                }
+            }
+        }
+
+        subsquares
+    }
+
+    pub fn subsquares_bitset_dyn(&self, k: usize) -> Vec<BitSet128> {
+        let mut subsquares = Vec::new();
+        let n = self.n;
+        assert!(n * n <= 128);
+
         for rows in TupleIteratorDyn::new(n, k) {
             for cols in TupleIteratorDyn::new(n, k) {
                 let mut subsquare = self.get_subsquare_dyn(&rows, &cols);
@@ -199,7 +572,7 @@ impl LatinSquareDyn {
                             == BitSet16::all_less_than(k)
                 });
                 if is_subsquare {
-                    subsquares += 1;
+                    subsquares.push(rows_cols_mask(&rows, &cols, n));
                 }
             }
         }
@@ -220,6 +593,23 @@ impl<const N: usize> From<LatinSquare<N>> for LatinSquareDyn {
     }
 }
 
+impl<const N: usize> TryFrom<&LatinSquareDyn> for LatinSquare<N> {
+    type Error = ();
+
+    fn try_from(value: &LatinSquareDyn) -> Result<Self, ()> {
+        if value.n() != N {
+            return Err(());
+        }
+
+        let mut rows = [[0; N]; N];
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.copy_from_slice(&value.values()[i * N..(i + 1) * N]);
+        }
+
+        Ok(LatinSquare::new(rows))
+    }
+}
+
 impl Display for LatinSquareDyn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let n = self.n();
@@ -233,11 +623,72 @@ impl Display for LatinSquareDyn {
     }
 }
 
+/// A violation of the latin square property
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    DuplicateInRow {
+        row: usize,
+        symbol: usize,
+    },
+    DuplicateInCol {
+        col: usize,
+        symbol: usize,
+    },
+    InvalidSymbol {
+        row: usize,
+        col: usize,
+        symbol: usize,
+    },
+    InvalidLength {
+        len: usize,
+    },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::DuplicateInRow { row, symbol } => {
+                write!(f, "row {row} contains symbol {symbol} more than once")
+            }
+            Conflict::DuplicateInCol { col, symbol } => {
+                write!(f, "col {col} contains symbol {symbol} more than once")
+            }
+            Conflict::InvalidSymbol { row, col, symbol } => {
+                write!(f, "symbol {symbol} at row {row}, col {col} is out of range")
+            }
+            Conflict::InvalidLength { len } => {
+                write!(f, "length {len} is not the square of an order up to 16")
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
-    InvalidLength { len: usize },
-    InvalidChar { index: usize, char: char },
+    InvalidLength {
+        len: usize,
+    },
+    InvalidChar {
+        index: usize,
+        char: char,
+    },
     InvalidLatinSquare,
+    InvalidJson {
+        index: usize,
+    },
+    InvalidRowLength {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    InvalidEntry {
+        row: usize,
+        col: usize,
+        entry: usize,
+    },
+    InvalidOrder {
+        n: usize,
+    },
 }
 
 impl Display for Error {
@@ -250,6 +701,14 @@ impl Display for Error {
                 write!(f, "Invalid char at index {index}: {char}")
             }
             Error::InvalidLatinSquare => write!(f, "The latin square property is not met"),
+            Error::InvalidJson { index } => write!(f, "Invalid JSON at index {index}"),
+            Error::InvalidRowLength { row, len, expected } => {
+                write!(f, "Invalid length of row {row}: {len}, expected {expected}")
+            }
+            Error::InvalidEntry { row, col, entry } => {
+                write!(f, "Invalid entry at row {row}, col {col}: {entry}")
+            }
+            Error::InvalidOrder { n } => write!(f, "Invalid order: {n}, expected at most 16"),
         }
     }
 }
@@ -308,3 +767,265 @@ pub fn isqrt(n: usize) -> Option<usize> {
     }
     unreachable!()
 }
+
+#[cfg(test)]
+mod test {
+
+    use crate::random_latin_square_generator::RandomLatinSquareGeneratorDyn;
+
+    use super::*;
+
+    #[test]
+    fn cycles() {
+        for sq in RandomLatinSquareGeneratorDyn::new(6, 66).take(5) {
+            let sq_n: LatinSquare<6> = (&sq).try_into().unwrap();
+
+            assert_eq!(sq.row_cycles(), sq_n.row_cycles());
+            assert_eq!(sq.col_cycles(), sq_n.col_cycles());
+            assert_eq!(sq.val_cycles(), sq_n.val_cycles());
+        }
+    }
+
+    #[test]
+    fn to_spaced_string() {
+        let n = 10;
+        let values = (0..n * n).map(|i| ((i / n + i % n) % n) as u8).collect();
+        let sq = LatinSquareDyn::from_boxed_slice(values).unwrap();
+
+        let spaced = sq.to_spaced_string();
+        assert_eq!(spaced.lines().next(), Some("0 1 2 3 4 5 6 7 8 9"));
+
+        let values: Vec<u32> = spaced
+            .split_whitespace()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        let hex: Vec<u32> = sq
+            .to_string()
+            .chars()
+            .map(|c| c.to_digit(16).unwrap())
+            .collect();
+        assert_eq!(values, hex);
+
+        let n = 11;
+        let values = (0..n * n).map(|i| ((i / n + i % n) % n) as u8).collect();
+        let sq = LatinSquareDyn::from_boxed_slice(values).unwrap();
+        assert_eq!(
+            sq.to_spaced_string().lines().nth(1),
+            Some("01 02 03 04 05 06 07 08 09 10 00")
+        );
+    }
+
+    #[test]
+    fn random_uniquely_completable_partial() {
+        for sq in RandomLatinSquareGeneratorDyn::new(5, 5).take(3) {
+            for seed in 0..3 {
+                let partial_sq = sq.random_uniquely_completable_partial(seed);
+
+                assert!(partial_sq.is_uniquely_completable_to(&sq));
+                assert!(partial_sq.num_entries() < 25);
+                assert_eq!(partial_sq, sq.random_uniquely_completable_partial(seed));
+
+                // no entry can be removed anymore
+                for i in 0..5 {
+                    for j in 0..5 {
+                        if partial_sq.get_partial(i, j).is_some() {
+                            let mut smaller = partial_sq.clone();
+                            smaller.set(i, j, None);
+                            assert!(!smaller.is_uniquely_completable_to(&sq));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn is_reduced() {
+        let reduced = LatinSquareDyn::try_from("0123103223013210").unwrap();
+        assert!(reduced.is_reduced());
+
+        let first_row_only = LatinSquareDyn::try_from("0123230110323210").unwrap();
+        assert!(!first_row_only.is_reduced());
+
+        let not_reduced = LatinSquareDyn::try_from("1032012332102301").unwrap();
+        assert!(!not_reduced.is_reduced());
+    }
+
+    #[test]
+    fn is_orthogonal_to() {
+        let a = LatinSquareDyn::try_from("0123103223013210").unwrap();
+        let b = LatinSquareDyn::try_from("0123230132101032").unwrap();
+        let c = LatinSquareDyn::try_from("0123123023013012").unwrap();
+
+        assert!(a.is_orthogonal_to(&b));
+        assert!(b.is_orthogonal_to(&a));
+        assert!(!a.is_orthogonal_to(&c));
+        assert!(!a.is_orthogonal_to(&a));
+    }
+
+    #[test]
+    fn subsquares_bitset_dyn() {
+        for sq in RandomLatinSquareGeneratorDyn::new(6, 6).take(10) {
+            for k in [2, 3] {
+                let subsquares = sq.subsquares_bitset_dyn(k);
+                assert_eq!(subsquares.len(), sq.num_subsquares_dyn(k));
+
+                for subsquare in subsquares {
+                    assert_eq!(subsquare.len(), k * k);
+                }
+            }
+        }
+
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+        assert_eq!(sq.subsquares_bitset_dyn(2).len(), 12);
+    }
+
+    #[test]
+    fn validation_report() {
+        let valid = [0, 1, 2, 1, 2, 0, 2, 0, 1];
+        assert_eq!(LatinSquareDyn::validation_report(&valid), Ok(()));
+
+        let duplicate_in_row = [0, 1, 2, 1, 2, 0, 2, 2, 1];
+        assert_eq!(
+            LatinSquareDyn::validation_report(&duplicate_in_row),
+            Err(vec![
+                Conflict::DuplicateInRow { row: 2, symbol: 2 },
+                Conflict::DuplicateInCol { col: 1, symbol: 2 },
+            ])
+        );
+
+        let duplicate_in_col = [0, 1, 2, 1, 2, 0, 0, 1, 2];
+        assert_eq!(
+            LatinSquareDyn::validation_report(&duplicate_in_col),
+            Err(vec![
+                Conflict::DuplicateInCol { col: 0, symbol: 0 },
+                Conflict::DuplicateInCol { col: 1, symbol: 1 },
+                Conflict::DuplicateInCol { col: 2, symbol: 2 },
+            ])
+        );
+
+        let out_of_range = [0, 1, 0, 3];
+        assert_eq!(
+            LatinSquareDyn::validation_report(&out_of_range),
+            Err(vec![
+                Conflict::InvalidSymbol {
+                    row: 1,
+                    col: 1,
+                    symbol: 3
+                },
+                Conflict::DuplicateInCol { col: 0, symbol: 0 },
+            ])
+        );
+
+        assert_eq!(
+            LatinSquareDyn::validation_report(&[0, 1, 1]),
+            Err(vec![Conflict::InvalidLength { len: 3 }])
+        );
+        assert_eq!(
+            LatinSquareDyn::validation_report(&[0; 17 * 17]),
+            Err(vec![Conflict::InvalidLength { len: 17 * 17 }])
+        );
+    }
+
+    #[test]
+    fn permuted_rcs() {
+        let sq = LatinSquareDyn::try_from("012201120").unwrap();
+        let transpose = LatinSquareDyn::try_from("021102210").unwrap();
+
+        assert_eq!(
+            sq.permuted_rcs(&Permutation::from_array([1, 0, 2])),
+            transpose
+        );
+        assert_eq!(sq.permuted_rcs(&Permutation::identity()), sq);
+
+        for sq in RandomLatinSquareGeneratorDyn::new(5, 5).take(5) {
+            let sq_n: LatinSquare<5> = (&sq).try_into().unwrap();
+            for (conjugate, conjugate_n) in sq.conjugates().zip(sq_n.conjugates()) {
+                assert_eq!(conjugate, LatinSquareDyn::from(conjugate_n));
+            }
+        }
+    }
+
+    #[test]
+    fn json() {
+        let sq = LatinSquareDyn::from_json("[[0,1,2],[1,2,0],[2,0,1]]").unwrap();
+        assert_eq!(sq.to_string(), "012120201");
+
+        let sq = LatinSquareDyn::from_json(" [ [0, 1],\n [1, 0] ]\n").unwrap();
+        assert_eq!(sq.to_json(), "[[0,1],[1,0]]");
+
+        for n in 3..=6 {
+            for sq in RandomLatinSquareGeneratorDyn::new(n, n as u64).take(5) {
+                assert_eq!(LatinSquareDyn::from_json(&sq.to_json()).unwrap(), sq);
+            }
+        }
+
+        assert!(matches!(
+            LatinSquareDyn::from_json("[[0,1,2],[1,2],[2,0,1]]"),
+            Err(Error::InvalidRowLength { row: 1, len: 2, .. })
+        ));
+        assert!(matches!(
+            LatinSquareDyn::from_json("[[0,1],[1,2]]"),
+            Err(Error::InvalidEntry { row: 1, col: 1, .. })
+        ));
+        assert!(matches!(
+            LatinSquareDyn::from_json("[[0,1],[0,1]]"),
+            Err(Error::InvalidLatinSquare)
+        ));
+        assert!(matches!(
+            LatinSquareDyn::from_json("[[0,1],[1,0]"),
+            Err(Error::InvalidJson { .. })
+        ));
+        for json in ["[[0,1,],[1,0]]", "[[0,1],[1,0],]", "[[0,1,],[1,0],]"] {
+            assert!(matches!(
+                LatinSquareDyn::from_json(json),
+                Err(Error::InvalidJson { .. })
+            ));
+        }
+        assert!(matches!(
+            LatinSquareDyn::from_json("[[0,1],[1,256]]"),
+            Err(Error::InvalidJson { .. })
+        ));
+
+        let rows = vec![format!("[{}]", vec!["0"; 17].join(",")); 17];
+        assert!(matches!(
+            LatinSquareDyn::from_json(&format!("[{}]", rows.join(","))),
+            Err(Error::InvalidOrder { n: 17 })
+        ));
+    }
+
+    #[test]
+    fn try_from_rows() {
+        let sq = LatinSquareDyn::try_from_rows(vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]])
+            .unwrap();
+        assert_eq!(sq.to_string(), "012120201");
+
+        for n in 1..=6 {
+            for sq in RandomLatinSquareGeneratorDyn::new(n, n as u64).take(5) {
+                let rows = (0..n).map(|i| sq.get_row(i).to_vec()).collect();
+                assert_eq!(LatinSquareDyn::try_from_rows(rows).unwrap(), sq);
+            }
+        }
+
+        assert!(matches!(
+            LatinSquareDyn::try_from_rows(vec![vec![0, 1, 2], vec![1, 2], vec![2, 0, 1]]),
+            Err(Error::InvalidRowLength {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        ));
+        assert!(matches!(
+            LatinSquareDyn::try_from_rows(vec![vec![0, 1], vec![1, 2]]),
+            Err(Error::InvalidEntry { row: 1, col: 1, .. })
+        ));
+        assert!(matches!(
+            LatinSquareDyn::try_from_rows(vec![vec![0, 1], vec![0, 1]]),
+            Err(Error::InvalidLatinSquare)
+        ));
+        assert!(matches!(
+            LatinSquareDyn::try_from_rows(vec![vec![0; 17]; 17]),
+            Err(Error::InvalidOrder { n: 17 })
+        ));
+    }
+}
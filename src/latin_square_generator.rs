@@ -1,6 +1,6 @@
 use crate::{
-    constraints::ConstraintsDyn, latin_square_dyn::LatinSquareDyn,
-    partial_latin_square_dyn::PartialLatinSquareDyn,
+    bitset::BitSet16, constraints::ConstraintsDyn, latin_square::LatinSquare,
+    latin_square_dyn::LatinSquareDyn, partial_latin_square_dyn::PartialLatinSquareDyn,
 };
 
 #[derive(Debug)]
@@ -9,12 +9,25 @@ pub struct LatinSquareGeneratorDyn {
 }
 
 impl LatinSquareGeneratorDyn {
+    /// Generates all latin squares of order `n`, not only the reduced ones
     pub fn new(n: usize) -> Self {
         LatinSquareGeneratorDyn {
             stack: vec![(ConstraintsDyn::new(n), 1, 1, 0)],
         }
     }
 
+    /// Generates the reduced latin squares of order `n`,
+    /// where the first row and column are `0, 1, ..., n-1`
+    pub fn new_reduced(n: usize) -> Self {
+        let mut sq = PartialLatinSquareDyn::empty(n);
+        for i in 0..n {
+            sq.set(0, i, Some(i));
+            sq.set(i, 0, Some(i));
+        }
+
+        Self::from_partial_sq(&sq)
+    }
+
     pub fn from_partial_sq(sq: &PartialLatinSquareDyn) -> Self {
         let mut constraints = ConstraintsDyn::new_partial(sq);
         constraints.find_singles();
@@ -77,3 +90,285 @@ impl Iterator for LatinSquareGeneratorDyn {
         None
     }
 }
+
+/// Generates the reduced latin squares of order `N` by a depth first search over the cells
+/// outside the first row and column. Unlike `LatinSquareGeneratorDyn` the row and column
+/// constraints are updated in place when setting and unsetting a cell instead of being cloned
+#[derive(Debug)]
+pub struct ReducedSquareWalker<const N: usize> {
+    rows: [[u8; N]; N],
+    /// The values missing in each row
+    row_values: [BitSet16; N],
+    /// The values missing in each column
+    col_values: [BitSet16; N],
+    /// The remaining candidates and the current value of each cell set so far
+    stack: Vec<(BitSet16, Option<u8>)>,
+    done: bool,
+}
+
+impl<const N: usize> ReducedSquareWalker<N> {
+    const NUM_CELLS: usize = N.saturating_sub(1) * N.saturating_sub(1);
+
+    pub fn new() -> Self {
+        assert!(N <= 16);
+
+        let mut rows = [[0; N]; N];
+        let mut row_values = [BitSet16::all_less_than(N); N];
+        let mut col_values = [BitSet16::all_less_than(N); N];
+
+        for i in 0..N {
+            rows[0][i] = i as u8;
+            rows[i][0] = i as u8;
+            row_values[0].remove(i);
+            col_values[0].remove(i);
+            if i != 0 {
+                row_values[i].remove(i);
+                col_values[i].remove(i);
+            }
+        }
+
+        let mut walker = ReducedSquareWalker {
+            rows,
+            row_values,
+            col_values,
+            stack: Vec::with_capacity(Self::NUM_CELLS),
+            done: false,
+        };
+        if Self::NUM_CELLS > 0 {
+            walker.stack.push((walker.candidates(0), None));
+        }
+
+        walker
+    }
+
+    fn cell(index: usize) -> (usize, usize) {
+        (1 + index / (N - 1), 1 + index % (N - 1))
+    }
+
+    fn candidates(&self, index: usize) -> BitSet16 {
+        let (i, j) = Self::cell(index);
+        self.row_values[i].intersect(self.col_values[j])
+    }
+}
+
+impl<const N: usize> Default for ReducedSquareWalker<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Iterator for ReducedSquareWalker<N> {
+    type Item = LatinSquare<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if Self::NUM_CELLS == 0 {
+            self.done = true;
+            return Some(LatinSquare::new(self.rows));
+        }
+
+        while !self.stack.is_empty() {
+            let index = self.stack.len() - 1;
+            let (i, j) = Self::cell(index);
+            let (candidates, current) = &mut self.stack[index];
+
+            if let Some(value) = current.take() {
+                self.row_values[i].insert(value.into());
+                self.col_values[j].insert(value.into());
+            }
+
+            let Some(value) = candidates.select(0) else {
+                self.stack.pop();
+                continue;
+            };
+            candidates.remove(value);
+            *current = Some(value as u8);
+
+            self.rows[i][j] = value as u8;
+            self.row_values[i].remove(value);
+            self.col_values[j].remove(value);
+
+            if index + 1 == Self::NUM_CELLS {
+                return Some(LatinSquare::new(self.rows));
+            }
+
+            let next_candidates = self.candidates(index + 1);
+            self.stack.push((next_candidates, None));
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+/// Generates the latin squares of order `n` whose main diagonal and anti-diagonal are transversals.
+/// The diagonals are constrained like rows and columns, so violating partial squares are never extended
+#[derive(Debug)]
+pub struct DiagonalSquareGenerator {
+    n: usize,
+    values: Box<[u8]>,
+    /// The values missing in each row
+    row_values: Box<[BitSet16]>,
+    /// The values missing in each column
+    col_values: Box<[BitSet16]>,
+    /// The values missing on the main diagonal and the anti-diagonal
+    diagonal_values: [BitSet16; 2],
+    /// The remaining candidates and the current value of each cell set so far
+    stack: Vec<(BitSet16, Option<u8>)>,
+}
+
+impl DiagonalSquareGenerator {
+    pub fn new(n: usize) -> Self {
+        assert!(n <= 16);
+
+        let mut generator = DiagonalSquareGenerator {
+            n,
+            values: vec![0; n * n].into_boxed_slice(),
+            row_values: vec![BitSet16::all_less_than(n); n].into_boxed_slice(),
+            col_values: vec![BitSet16::all_less_than(n); n].into_boxed_slice(),
+            diagonal_values: [BitSet16::all_less_than(n); 2],
+            stack: Vec::with_capacity(n * n),
+        };
+        if n > 0 {
+            generator.stack.push((generator.candidates(0), None));
+        }
+
+        generator
+    }
+
+    fn candidates(&self, index: usize) -> BitSet16 {
+        let (i, j) = (index / self.n, index % self.n);
+
+        let mut candidates = self.row_values[i].intersect(self.col_values[j]);
+        if i == j {
+            candidates = candidates.intersect(self.diagonal_values[0]);
+        }
+        if i + j == self.n - 1 {
+            candidates = candidates.intersect(self.diagonal_values[1]);
+        }
+
+        candidates
+    }
+
+    fn update(&mut self, index: usize, value: usize, insert: bool) {
+        let (i, j) = (index / self.n, index % self.n);
+
+        let update = |set: &mut BitSet16| {
+            if insert {
+                set.insert(value);
+            } else {
+                set.remove(value);
+            }
+        };
+
+        update(&mut self.row_values[i]);
+        update(&mut self.col_values[j]);
+        if i == j {
+            update(&mut self.diagonal_values[0]);
+        }
+        if i + j == self.n - 1 {
+            update(&mut self.diagonal_values[1]);
+        }
+    }
+}
+
+impl Iterator for DiagonalSquareGenerator {
+    type Item = LatinSquareDyn;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.stack.is_empty() {
+            let index = self.stack.len() - 1;
+            let (candidates, current) = &mut self.stack[index];
+
+            let previous = current.take();
+            let next = candidates.select(0);
+            if let Some(value) = next {
+                candidates.remove(value);
+                *current = Some(value as u8);
+            }
+
+            if let Some(value) = previous {
+                self.update(index, value.into(), true);
+            }
+
+            let Some(value) = next else {
+                self.stack.pop();
+                continue;
+            };
+
+            self.values[index] = value as u8;
+            self.update(index, value, false);
+
+            if index + 1 == self.n * self.n {
+                return LatinSquareDyn::from_boxed_slice(self.values.clone());
+            }
+
+            let next_candidates = self.candidates(index + 1);
+            self.stack.push((next_candidates, None));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn count() {
+        assert_eq!(LatinSquareGeneratorDyn::new(4).count(), 576);
+        assert_eq!(LatinSquareGeneratorDyn::new(5).count(), 161280);
+
+        assert_eq!(LatinSquareGeneratorDyn::new_reduced(4).count(), 4);
+        assert_eq!(LatinSquareGeneratorDyn::new_reduced(5).count(), 56);
+        assert_eq!(LatinSquareGeneratorDyn::new_reduced(6).count(), 9408);
+
+        for sq in LatinSquareGeneratorDyn::new_reduced(5) {
+            assert!(sq.is_reduced());
+        }
+    }
+
+    #[test]
+    fn diagonal_square_generator() {
+        assert_eq!(DiagonalSquareGenerator::new(1).count(), 1);
+        assert_eq!(DiagonalSquareGenerator::new(2).count(), 0);
+        assert_eq!(DiagonalSquareGenerator::new(3).count(), 0);
+        assert_eq!(DiagonalSquareGenerator::new(4).count(), 48);
+        assert_eq!(DiagonalSquareGenerator::new(5).count(), 960);
+
+        let sqs: HashSet<_> = DiagonalSquareGenerator::new(4).collect();
+        assert_eq!(sqs.len(), 48);
+        for sq in &sqs {
+            assert!(LatinSquare::<4>::try_from(sq).unwrap().is_diagonal());
+        }
+
+        let expected = LatinSquareGeneratorDyn::new(4)
+            .filter(|sq| LatinSquare::<4>::try_from(sq).unwrap().is_diagonal())
+            .count();
+        assert_eq!(expected, 48);
+    }
+
+    #[test]
+    fn reduced_square_walker() {
+        assert_eq!(ReducedSquareWalker::<1>::new().count(), 1);
+        assert_eq!(ReducedSquareWalker::<2>::new().count(), 1);
+        assert_eq!(ReducedSquareWalker::<3>::new().count(), 1);
+        assert_eq!(ReducedSquareWalker::<4>::new().count(), 4);
+        assert_eq!(ReducedSquareWalker::<5>::new().count(), 56);
+        assert_eq!(ReducedSquareWalker::<6>::new().count(), 9408);
+
+        let sqs: HashSet<_> = ReducedSquareWalker::<5>::new()
+            .map(LatinSquareDyn::from)
+            .collect();
+        let expected: HashSet<_> = LatinSquareGeneratorDyn::new_reduced(5).collect();
+        assert_eq!(sqs.len(), 56);
+        assert_eq!(sqs, expected);
+    }
+}
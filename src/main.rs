@@ -1,32 +1,41 @@
 #![feature(portable_simd)]
+#![feature(reentrant_lock)]
 
 use std::{
     collections::{BinaryHeap, HashMap, HashSet},
-    io::{stdin, stdout, Read, Write},
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
     sync::Arc,
     thread::{self},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use bitset::{BitSet128, BitSet16};
 use clap::{self, Parser, Subcommand};
 
+use constraints::ConstraintsDyn;
 use cycles::{generate_minimize_rows_lookup, generate_minimize_rows_lookup_simd};
 use isotopy_class_generator::IsotopyClassGenerator;
 use latin_square::LatinSquare;
 
-use latin_square_dyn::LatinSquareDyn;
-use latin_square_generator::LatinSquareGeneratorDyn;
+use latin_square_dyn::{isqrt, LatinSquareDyn};
+use latin_square_generator::{
+    DiagonalSquareGenerator, LatinSquareGeneratorDyn, ReducedSquareWalker,
+};
 
 use mmcs_hitting_set_generator::MMCSHittingSetGenerator;
 
 use mols::Mols;
 use partial_latin_square_dyn::PartialLatinSquareDyn;
 use partial_square_generator::PartialSquareGeneratorDyn;
-use permutation::{factorial, Permutation};
+use permutation::{factorial, Permutation, PermutationIter};
 use permutation_dyn::PermutationDyn;
+use progress::progress;
 use random_latin_square_generator::RandomLatinSquareGeneratorDyn;
+use redirect::{input, output};
 use threaded_main_class_generator::ThreadedMainClassGenerator;
+use uniform_random_generator::UniformLatinSquareGenerator;
 
 mod bitset;
 mod bitvec;
@@ -38,26 +47,68 @@ mod latin_square_dyn;
 mod latin_square_generator;
 mod mmcs_hitting_set_generator;
 mod mols;
+mod orthogonal_array;
 mod partial_latin_square;
 mod partial_latin_square_dyn;
 mod partial_square_generator;
 mod permutation;
 mod permutation_dyn;
 mod permutation_simd;
+mod progress;
 mod random_latin_square_generator;
+mod redirect;
 mod row_partial_latin_square;
 mod threaded_main_class_generator;
 mod tuple_iterator;
+mod uniform_random_generator;
 
 #[derive(Subcommand, Clone)]
 enum Mode {
     /// Prints a latin square in a 2D grid
-    PrettyPrint,
+    PrettyPrint {
+        /// Print complete squares as space separated decimal values
+        #[arg(long)]
+        spaced: bool,
+    },
+    /// Reads pairs of latin squares and prints a grid with the differing cells marked as `X`
+    Diff,
     /// Prints all solutions for a partial latin square
     Solve,
+    /// Prints the number of completions of each partial latin square
+    CountCompletions {
+        /// Stop counting at this many completions and print `>=limit`
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Prints the total number of candidates over all empty cells of each partial latin square
+    /// and the empty cell with the fewest candidates as `total row,col:candidates`
+    Difficulty,
+    /// Prints the first solution for a partial latin square, or `UNSAT` if there is none
+    SolveOne,
+    /// Completes latin rectangles (the first rows filled, the others empty) to latin squares
+    CompleteRectangle,
     CountSubsquares {
         k: usize,
     },
+    /// Prints for each symbol the permutation mapping rows to the columns containing it
+    ValuePerms {
+        n: usize,
+    },
+    /// Reads MOLS and reports whether they are complete, valid but incomplete or not orthogonal
+    CheckMols {
+        n: usize,
+    },
+    /// Reads MOLS and prints the unavoidable sets of order 1 of their orthogonal array,
+    /// each as a hex mask followed by the cells `row,col`
+    UnavoidableSets {
+        n: usize,
+    },
+    /// Prints the sorted row, column and symbol cycle structures of latin squares
+    Cycles,
+    /// Prints each k-subsquare of the latin squares as a partial square
+    SubsquareMap {
+        k: usize,
+    },
     CountEntries,
     /// Counts the number of isotopy classes in the given main classes
     CountIsotopyClasses {
@@ -67,8 +118,14 @@ enum Mode {
         #[arg(long, default_value_t = 10000)]
         buffer_size: usize,
     },
+    /// Prints how many squares have each number of transversals
+    TransversalHistogram {
+        n: usize,
+    },
     CountTransversals {
         n: usize,
+        #[arg(long, default_value_t = 1)]
+        max_threads: usize,
     },
     Transversals {
         n: usize,
@@ -77,57 +134,149 @@ enum Mode {
         n: usize,
         k: usize,
     },
+    /// Prints the number of k-subsquares containing a sub-transversal
+    /// and the total number of sub-transversals for each latin square
+    CountSubTransversals {
+        n: usize,
+        k: usize,
+    },
     MainClassSize {
         n: usize,
     },
     /// Prints information about a latin square
     Analyse {
         n: usize,
+        #[arg(long)]
+        check_symmetric: bool,
+    },
+    /// Prints the number of intercalates, the number of transversals and the row cycle structure
+    /// of latin squares, which are equal for isotopic squares
+    IsotopyInvariant {
+        n: usize,
+    },
+    /// Prints information about latin squares as one JSON object per line
+    Stats {
+        n: usize,
+    },
+    /// Checks whether a latin square is the Cayley table of a group
+    GroupTest {
+        n: usize,
     },
     /// Prints the main class representative of a latin square
     NormalizeMainClass {
         n: usize,
+        /// Also print the conjugate and the row, column and symbol permutations
+        /// mapping the square to its representative
+        #[arg(long)]
+        all: bool,
     },
     NormalizeMOLS {
         n: usize,
     },
+    /// Reads pairs of latin squares and prints whether they are isotopic,
+    /// in the same main class or distinct
+    Equivalent {
+        n: usize,
+        #[arg(long)]
+        main_class: bool,
+    },
     /// Generates all latin squares of an order n
     GenerateLatinSquares {
         n: usize,
     },
+    /// Counts all latin squares of an order n without printing them
+    CountLatinSquares {
+        n: usize,
+        /// Only count the reduced latin squares
+        #[arg(long)]
+        reduced: bool,
+    },
     /// Generates a representative of each isotopy class of an order n
     GenerateIsotopyClasses {
         n: usize,
+        /// Skips the representatives in this file, e.g. the output of an interrupted run
+        #[arg(long)]
+        skip: Option<PathBuf>,
+        /// Periodically prints the number of emitted and skipped representatives to stderr,
+        /// implies `--verbose`
+        #[arg(long, conflicts_with = "quiet")]
+        emit_progress: bool,
     },
     /// Generates a representative of each main class of an order n
     GenerateMainClasses {
         n: usize,
         #[arg(long, default_value_t = 1)]
         max_threads: usize,
+        /// Check each representative against the slow brute force normalization
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Counts the main classes of order n by their number of intercalates
+    CountMainClasses {
+        n: usize,
+        #[arg(long, default_value_t = 1)]
+        max_threads: usize,
     },
     /// Generates all critical sets for a latin square in a binary format.
     /// The resulting data can be decoded with `decode-cs`
-    FindAllCS,
+    FindAllCS {
+        /// Print the square followed by each critical set as a partial square instead
+        #[arg(long)]
+        text: bool,
+    },
+    /// Reads pairs of a latin square and a partial latin square and checks whether the
+    /// partial square is a critical set of the latin square
+    CheckCriticalSet,
     FindSCS {
         #[arg(short, long)]
         reverse: bool,
+        /// Print all critical sets of the smallest size instead of only one
+        #[arg(long, conflicts_with = "reverse")]
+        all: bool,
     },
     FindLCS {
         #[arg(long, default_value_t = 1)]
         max_threads: usize,
+        /// Only consider critical sets with at most this many entries
+        #[arg(long)]
+        max_size: Option<usize>,
     },
     FindAllUC {
         #[arg(short, long)]
         brute_force: bool,
     },
+    /// Reads pairs of latin squares and prints whether they are orthogonal
+    CheckOrthogonal,
+    /// Reads pairs of latin squares and prints the number of cells in which they differ
+    Distance,
     FindOrthogonal {
         n: usize,
         #[arg(short, long)]
         all: bool,
+        /// Only print whether an orthogonal mate exists
+        #[arg(long, conflicts_with = "all")]
+        exists: bool,
     },
     FindMOLS {
         n: usize,
         mols: usize,
+        /// Print each set of MOLS as an orthogonal array
+        #[arg(long)]
+        oa: bool,
+    },
+    /// Extends each set of MOLS to sets of `target` MOLS
+    ExtendMols {
+        n: usize,
+        target: usize,
+    },
+    /// Reads all latin squares and prints for each the indices of the other squares it is orthogonal to.
+    /// This compares every pair of squares, so it takes O(m^2 n^2) time for m squares
+    OrthogonalityGraph {
+        n: usize,
+    },
+    /// Greedily extends each square to a set of MOLS, this only gives a lower bound
+    GreedyMols {
+        n: usize,
     },
     FindAllMOLS {
         n: usize,
@@ -135,25 +284,121 @@ enum Mode {
         max_threads: usize,
         #[arg(long, default_value_t = 10)]
         buffer_size: usize,
+        /// Stop searching each square after this many seconds and print what was found
+        #[arg(long)]
+        timeout_secs: Option<u64>,
     },
     ToTex {
         #[arg(long, default_value_t = false)]
         standalone: bool,
+        /// Transpose the squares before drawing them
+        #[arg(long)]
+        transpose: bool,
+    },
+    /// Prints latin squares as comma separated values, one row per line
+    ToCsv {
+        #[arg(long)]
+        one_indexed: bool,
     },
+    /// Checks each line of the input and reports why it is not a latin square
+    Validate,
+    /// Reads latin squares as JSON arrays of rows, like `[[0,1],[1,0]]`
+    FromJson,
+    /// Prints latin squares as JSON arrays of rows
+    ToJson,
     Encode {
         n: usize,
     },
     Decode {
         n: usize,
     },
+    /// Encodes squares of any order, each prefixed by a byte holding its order
+    EncodeDyn,
+    /// Decodes the output of `encode-dyn`
+    DecodeDyn,
     DecodeCS,
+    /// Prints a hash of the main class that is stable across runs, followed by the main class representative
+    Canonicalize {
+        n: usize,
+    },
+    /// Prints one representative of each main class in the input together with its number of squares
+    DedupMainClass {
+        n: usize,
+    },
     Expand {
         n: usize,
+        /// Only compute the orthogonal mates of each main class once
+        #[arg(long)]
+        cache: bool,
     },
     // Generates pseudo-random latin squares
     Random {
         n: usize,
         seed: u64,
+        /// Stop after this many squares instead of running forever
+        #[arg(long)]
+        count: Option<usize>,
+    },
+    /// Generates uniformly distributed random latin squares
+    RandomUniform {
+        n: usize,
+        seed: u64,
+        /// Number of Jacobson-Matthews moves between two samples, defaults to n^3
+        #[arg(long)]
+        steps: Option<usize>,
+    },
+    /// Prints the conjugate of each latin square
+    Conjugate {
+        /// The new roles of rows, columns and symbols, e.g. `CRS` for the transpose
+        #[arg(value_parser = parse_rcs)]
+        rcs: Permutation<3>,
+    },
+    /// Reduces latin squares by permuting the symbols and rows
+    Reduce {
+        n: usize,
+    },
+    /// Prints all latin squares obtained by switching one intercalate
+    Neighbors {
+        n: usize,
+    },
+    /// Removes entries of each latin square in a random order while it stays uniquely completable
+    RandomPartial {
+        seed: u64,
+    },
+    /// Only outputs the reduced latin squares
+    FilterReduced,
+    /// Only outputs the latin squares without intercalates
+    FilterIntercalateFree {
+        n: usize,
+    },
+    /// Transposes (partial) latin squares, keeping empty cells empty
+    Transpose,
+    /// Prints the distinct conjugates of latin squares, labeled by the order of rows, columns and symbols
+    Conjugates {
+        n: usize,
+    },
+    /// Prints all complete mappings of the cyclic group of order n
+    /// together with the latin square they define
+    CompleteMappings {
+        n: usize,
+    },
+    /// Generates all latin squares of order n whose main diagonal and anti-diagonal are transversals
+    GenerateDiagonal {
+        n: usize,
+    },
+    /// Only outputs the latin squares whose main diagonal and anti-diagonal are transversals
+    FilterDiagonal {
+        n: usize,
+    },
+    /// Applies the given row, column and symbol permutations, each written as
+    /// the comma separated images of 0..n
+    Apply {
+        #[arg(long)]
+        rows: Option<String>,
+        #[arg(long)]
+        cols: Option<String>,
+        #[arg(long)]
+        vals: Option<String>,
     },
     /// Permutes the symbols of a latin square randomly
     Shuffle {
@@ -163,8 +408,16 @@ enum Mode {
         c: bool,
         #[arg(short)]
         s: bool,
+        /// The permutations are drawn by a Fisher-Yates shuffle, so the output for a seed
+        /// differs from older versions, which drew the rank of each permutation
         #[arg(long)]
         seed: u64,
+        /// Print the ranks of the applied row, column and symbol permutations to stderr
+        #[arg(long)]
+        log: bool,
+        /// Transpose the squares after permuting them
+        #[arg(long)]
+        transpose: bool,
     },
 }
 
@@ -172,11 +425,45 @@ enum Mode {
 struct Args {
     #[command(subcommand)]
     mode: Mode,
+    /// Print progress messages to stderr
+    #[arg(long, short, global = true, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Only print results (default)
+    #[arg(long, short, global = true)]
+    quiet: bool,
+    /// Read the input from a file instead of stdin
+    #[arg(long, global = true)]
+    input: Option<PathBuf>,
+    /// Write the output to a file instead of stdout
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
 }
 
 fn main() {
     let args = Args::parse();
 
+    let emit_progress = matches!(
+        args.mode,
+        Mode::GenerateIsotopyClasses {
+            emit_progress: true,
+            ..
+        }
+    );
+    progress::set_verbose((args.verbose || emit_progress) && !args.quiet);
+
+    if let Some(path) = &args.input {
+        if let Err(err) = redirect::set_input(path) {
+            eprintln!("Could not open {}: {err}", path.display());
+            return;
+        }
+    }
+    if let Some(path) = &args.output {
+        if let Err(err) = redirect::set_output(path) {
+            eprintln!("Could not create {}: {err}", path.display());
+            return;
+        }
+    }
+
     macro_rules! match_n {
         ($n: expr, $f: ident $(, $args: expr)*) => {
             match $n {
@@ -199,109 +486,415 @@ fn main() {
     }
 
     match args.mode {
-        Mode::Analyse { n } => match_n!(n, analyse),
+        Mode::Analyse { n, check_symmetric } => match_n!(n, analyse, check_symmetric),
+        Mode::Stats { n } => match_n!(n, stats),
+        Mode::IsotopyInvariant { n } => match_n!(n, isotopy_invariant),
         Mode::CountSubsquares { k } => count_subsquares(k),
+        Mode::ValuePerms { n } => match_n!(n, value_perms),
+        Mode::CheckMols { n } => match_n!(n, check_mols),
+        Mode::UnavoidableSets { n } => match_n!(n, unavoidable_sets),
+        Mode::Cycles => cycles(),
+        Mode::SubsquareMap { k } => subsquare_map(k),
         Mode::CountEntries => count_entries(),
         Mode::CountIsotopyClasses {
             n,
             max_threads,
             buffer_size,
         } => match_n!(n, count_isotopy_classes, max_threads, buffer_size),
-        Mode::CountTransversals { n } => match_n!(n, count_transversals),
+        Mode::TransversalHistogram { n } => match_n!(n, transversal_histogram),
+        Mode::CountTransversals { n, max_threads } => {
+            match_n!(n, count_transversals, max_threads)
+        }
         Mode::Transversals { n } => match_n!(n, transversals),
         Mode::SubTransversals { n, k } => match_n!(n, sub_transversals, k),
+        Mode::CountSubTransversals { n, k } => match_n!(n, count_sub_transversals, k),
         Mode::MainClassSize { n } => match_n!(n, main_class_size),
-        Mode::PrettyPrint => pretty_print(),
-        Mode::NormalizeMainClass { n } => match_n!(n, normalize_main_class),
+        Mode::GroupTest { n } => match_n!(n, group_test),
+        Mode::PrettyPrint { spaced } => pretty_print(spaced),
+        Mode::Diff => diff(),
+        Mode::NormalizeMainClass { n, all } => match_n!(n, normalize_main_class, all),
         Mode::NormalizeMOLS { n } => match_n!(n, normalize_mols),
+        Mode::Equivalent { n, main_class } => match_n!(n, equivalent, main_class),
         Mode::GenerateLatinSquares { n } => generate_latin_squares(n),
-        Mode::GenerateIsotopyClasses { n } => match_n!(n, generate_isotopy_classes),
-        Mode::GenerateMainClasses { n, max_threads } => {
-            match_n!(n, generate_main_classes, max_threads)
+        Mode::CountLatinSquares {
+            n: n @ 1..=11,
+            reduced: true,
+        } => match_n!(n, count_reduced_latin_squares),
+        Mode::CountLatinSquares { n, reduced } => count_latin_squares(n, reduced),
+        Mode::GenerateIsotopyClasses {
+            n,
+            skip,
+            emit_progress,
+        } => match_n!(n, generate_isotopy_classes, skip, emit_progress),
+        Mode::GenerateMainClasses {
+            n,
+            max_threads,
+            verify,
+        } => {
+            match_n!(n, generate_main_classes, max_threads, verify)
+        }
+        Mode::CountMainClasses { n, max_threads } => {
+            match_n!(n, count_main_classes, max_threads)
         }
         Mode::Solve => solve(),
-        Mode::Shuffle { r, c, s, seed } => shuffle(seed, r, c, s),
-        Mode::FindAllCS => find_all_cs(),
-        Mode::FindLCS { max_threads } => find_lcs(max_threads),
-        Mode::FindSCS { reverse } => find_scs(reverse),
+        Mode::CountCompletions { limit } => count_completions(limit),
+        Mode::Difficulty => difficulty(),
+        Mode::SolveOne => solve_one(),
+        Mode::CompleteRectangle => complete_rectangle(),
+        Mode::Conjugate { rcs } => conjugate(&rcs),
+        Mode::Reduce { n } => match_n!(n, reduce),
+        Mode::Neighbors { n } => match_n!(n, neighbors),
+        Mode::RandomPartial { seed } => random_partial(seed),
+        Mode::FilterReduced => filter_reduced(),
+        Mode::FilterIntercalateFree { n } => match_n!(n, filter_intercalate_free),
+        Mode::Transpose => transpose(),
+        Mode::Conjugates { n } => match_n!(n, conjugates),
+        Mode::CompleteMappings { n } => match_n!(n, complete_mappings),
+        Mode::GenerateDiagonal { n } => generate_diagonal(n),
+        Mode::FilterDiagonal { n } => match_n!(n, filter_diagonal),
+        Mode::Apply { rows, cols, vals } => apply(rows, cols, vals),
+        Mode::Shuffle {
+            r,
+            c,
+            s,
+            seed,
+            log,
+            transpose,
+        } => shuffle(seed, r, c, s, log, transpose),
+        Mode::FindAllCS { text } => find_all_cs(text),
+        Mode::FindLCS {
+            max_threads,
+            max_size,
+        } => find_lcs(max_threads, max_size),
+        Mode::CheckCriticalSet => check_critical_set(),
+        Mode::FindSCS { reverse, all } => find_scs(reverse, all),
         Mode::FindAllUC { brute_force } => find_all_uc(brute_force),
-        Mode::Random { n, seed } => random_latin_squares(n, seed),
-        Mode::FindOrthogonal { n, all } => match_n!(n, find_orthogonal, all),
-        Mode::FindMOLS { n, mols } => match_n!(n, find_mols, mols),
+        Mode::Random { n, seed, count } => random_latin_squares(n, seed, count),
+        Mode::RandomUniform { n, seed, steps } => {
+            random_uniform_latin_squares(n, seed, steps.unwrap_or(n.pow(3)))
+        }
+        Mode::CheckOrthogonal => check_orthogonal(),
+        Mode::Distance => distance(),
+        Mode::FindOrthogonal { n, all, exists } => match_n!(n, find_orthogonal, all, exists),
+        Mode::FindMOLS { n, mols, oa } => match_n!(n, find_mols, mols, oa),
+        Mode::ExtendMols { n, target } => match_n!(n, extend_mols, target),
+        Mode::OrthogonalityGraph { n } => match_n!(n, orthogonality_graph),
+        Mode::GreedyMols { n } => match_n!(n, greedy_mols),
         Mode::FindAllMOLS {
             n,
             max_threads,
             buffer_size,
-        } => match_n!(n, find_all_mols, max_threads, buffer_size),
-        Mode::ToTex { standalone } => to_tex(standalone),
+            timeout_secs,
+        } => match_n!(n, find_all_mols, max_threads, buffer_size, timeout_secs),
+        Mode::ToTex {
+            standalone,
+            transpose,
+        } => to_tex(standalone, transpose),
+        Mode::ToCsv { one_indexed } => to_csv(one_indexed),
+        Mode::Validate => validate(),
+        Mode::FromJson => from_json(),
+        Mode::ToJson => to_json(),
         Mode::Encode { n } => match_n!(n, encode),
         Mode::Decode { n } => match_n!(n, decode),
+        Mode::EncodeDyn => encode_dyn(),
+        Mode::DecodeDyn => decode_dyn(),
         Mode::DecodeCS => decode_cs(),
-        Mode::Expand { n } => match_n!(n, expand),
+        Mode::Canonicalize { n } => match_n!(n, canonicalize),
+        Mode::DedupMainClass { n } => match_n!(n, dedup_main_class),
+        Mode::Expand { n, cache } => match_n!(n, expand, cache),
+    }
+
+    match redirect::flush() {
+        Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => {
+            eprintln!("Could not write the output: {err}")
+        }
+        _ => {}
     }
 }
 
 fn count_subsquares(k: usize) {
     while let Some(sq) = read_sq_from_stdin() {
-        println!("{}", sq.num_subsquares_dyn(k));
+        writeln!(output(), "{}", sq.num_subsquares_dyn(k)).unwrap();
+    }
+}
+
+fn check_mols<const N: usize>() {
+    let mut line = String::new();
+    while input().read_line(&mut line).is_ok_and(|i| i != 0) {
+        let status = match Mols::<N>::try_from(line.trim()) {
+            Ok(mols) if mols.is_complete() => "complete",
+            Ok(_) => "valid but incomplete",
+            Err(mols::Error::NotOrthogonal { .. }) => "not orthogonal",
+            Err(err) => {
+                eprintln!("{err}");
+                line.clear();
+                continue;
+            }
+        };
+        line.clear();
+
+        if writeln!(output(), "{status}").is_err() {
+            return;
+        }
+    }
+}
+
+fn unavoidable_sets<const N: usize>() {
+    while let Some(mols) = read_mols_from_stdin::<N>() {
+        let mut stdout = output();
+        for set in mols.to_orthogonal_array().unavoidable_sets_order_1() {
+            if writeln!(stdout, "{}", format_cells(set, N)).is_err() {
+                return;
+            }
+        }
+        if writeln!(stdout).is_err() {
+            return;
+        }
+    }
+}
+
+/// Formats `mask` as its hex value followed by the cells `row,col`
+fn format_cells(mask: BitSet128, n: usize) -> String {
+    let cells: Vec<_> = mask
+        .cells(n)
+        .map(|(row, col)| format!("{row},{col}"))
+        .collect();
+
+    format!("{} {}", mask.to_hex(), cells.join(" "))
+}
+
+fn value_perms<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let mut stdout = output();
+        if writeln!(stdout, "{sq}").is_err() {
+            return;
+        }
+        for perm in sq.value_permutations() {
+            if writeln!(stdout, "{:?}", perm.as_array()).is_err() {
+                return;
+            }
+        }
+        if writeln!(stdout).is_err() {
+            return;
+        }
+    }
+}
+
+fn cycles() {
+    while let Some(sq) = read_sq_from_stdin() {
+        let mut stdout = output();
+        if writeln!(stdout, "{sq}").is_err() {
+            return;
+        }
+        for cycles in [sq.row_cycles(), sq.col_cycles(), sq.val_cycles()] {
+            if writeln!(stdout, "{cycles:?}").is_err() {
+                return;
+            }
+        }
+        if writeln!(stdout).is_err() {
+            return;
+        }
+    }
+}
+
+fn subsquare_map(k: usize) {
+    while let Some(sq) = read_sq_from_stdin() {
+        let mut stdout = output();
+        if writeln!(stdout, "{sq}").is_err() {
+            return;
+        }
+        for partial_sq in subsquare_partials(&sq, k) {
+            if writeln!(stdout, "{partial_sq}").is_err() {
+                return;
+            }
+        }
+        if writeln!(stdout).is_err() {
+            return;
+        }
+    }
+}
+
+fn subsquare_partials(sq: &LatinSquareDyn, k: usize) -> Vec<PartialLatinSquareDyn> {
+    sq.subsquares_bitset_dyn(k)
+        .into_iter()
+        .map(|subsquare| sq.mask(subsquare))
+        .collect()
+}
+
+fn check_orthogonal() {
+    while let (Some(a), Some(b)) = (read_sq_from_stdin(), read_sq_from_stdin()) {
+        if a.n() != b.n() {
+            eprintln!("Mismatched sizes: {} and {}", a.n(), b.n());
+            continue;
+        }
+
+        let result = if a.is_orthogonal_to(&b) {
+            "orthogonal"
+        } else {
+            "not orthogonal"
+        };
+
+        if writeln!(output(), "{result}").is_err() {
+            return;
+        }
     }
 }
 
-fn find_orthogonal<const N: usize>(all: bool) {
+fn distance() {
+    while let (Some(a), Some(b)) = (read_sq_from_stdin(), read_sq_from_stdin()) {
+        if a.n() != b.n() {
+            eprintln!("Mismatched sizes: {} and {}", a.n(), b.n());
+            continue;
+        }
+
+        if writeln!(output(), "{}", a.hamming_distance(&b)).is_err() {
+            return;
+        }
+    }
+}
+
+fn find_orthogonal<const N: usize>(all: bool, exists: bool) {
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
-        println!("{sq}");
+        if exists {
+            let result = if sq.has_orthogonal_mate() {
+                "yes"
+            } else {
+                "no"
+            };
+            if writeln!(output(), "{result}").is_err() {
+                return;
+            }
+            continue;
+        }
+
+        writeln!(output(), "{sq}").unwrap();
 
         if all {
             for sq in sq.orthogonal_squares() {
-                println!("{sq}");
+                writeln!(output(), "{sq}").unwrap();
             }
         } else if let Some(sq) = sq.orthogonal_squares().next() {
-            println!("{sq}");
+            writeln!(output(), "{sq}").unwrap();
+        }
+
+        writeln!(output()).unwrap()
+    }
+}
+
+fn random_latin_squares(n: usize, seed: u64, count: Option<usize>) {
+    for sq in RandomLatinSquareGeneratorDyn::new(n, seed).take(count.unwrap_or(usize::MAX)) {
+        if writeln!(output(), "{}", sq).is_err() {
+            return;
+        }
+    }
+}
+
+/// Counts the even and odd permutations mapping one row to another, for each pair of rows
+fn row_permutation_parities<const N: usize>(sq: &LatinSquare<N>) -> (usize, usize) {
+    let mut even = 0;
+    let mut odd = 0;
+
+    for i in 0..N {
+        for j in i + 1..N {
+            let mut permutation = [0; N];
+            for col in 0..N {
+                permutation[sq.get(i, col)] = sq.get(j, col);
+            }
+
+            if Permutation::from_array(permutation).is_even() {
+                even += 1;
+            } else {
+                odd += 1;
+            }
+        }
+    }
+
+    (even, odd)
+}
+
+fn random_uniform_latin_squares(n: usize, seed: u64, steps: usize) {
+    if n == 0 {
+        eprintln!("n has to be at least 1");
+        return;
+    }
+
+    for sq in UniformLatinSquareGenerator::new(n, seed, steps) {
+        if writeln!(output(), "{}", sq).is_err() {
+            return;
         }
+    }
+}
 
-        println!()
+fn isotopy_invariant<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        if writeln!(output(), "{}", sq.isotopy_invariants()).is_err() {
+            return;
+        }
     }
 }
 
-fn random_latin_squares(n: usize, seed: u64) {
-    for sq in RandomLatinSquareGeneratorDyn::new(n, seed) {
-        if writeln!(stdout(), "{}", sq).is_err() {
+fn stats<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        if writeln!(output(), "{}", stats_json(&sq)).is_err() {
             return;
         }
     }
 }
 
-fn analyse<const N: usize>() {
+fn stats_json<const N: usize>(sq: &LatinSquare<N>) -> String {
+    format!(
+        "{{\"n\":{},\"transversals\":{},\"intercalates\":{},\"max_disjoint_transversals\":{},\"num_symmetries\":{},\"is_reduced\":{}}}",
+        N,
+        sq.num_transversals(),
+        sq.num_intercalates(),
+        sq.max_disjoint_transversals(),
+        sq.symmetries().len(),
+        sq.is_reduced()
+    )
+}
+
+fn analyse<const N: usize>(check_symmetric: bool) {
     let lookup = generate_minimize_rows_lookup();
 
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
         pretty_print_sq_n(sq);
 
+        if check_symmetric {
+            writeln!(output(), "Symmetric: {}", sq.is_symmetric()).unwrap();
+            writeln!(output()).unwrap();
+        }
+
         for i in 2..N {
-            println!("Subsquares order {i}: {}", sq.num_subsquares(i));
+            writeln!(output(), "Subsquares order {i}: {}", sq.num_subsquares(i)).unwrap();
         }
-        println!();
+        writeln!(output()).unwrap();
 
-        println!("Symmetries: ");
+        writeln!(output(), "Symmetries: ").unwrap();
         let symmetries = sq.symmetries();
         for symmetry in symmetries {
             let rcs: String = symmetry.apply_array(['R', 'C', 'S']).into_iter().collect();
-            println!("{rcs}");
+            writeln!(output(), "{rcs}").unwrap();
         }
-        println!();
+        writeln!(output()).unwrap();
 
-        println!("Transversals: {}", sq.num_transversals());
-        println!(
+        writeln!(output(), "Autotopisms: {}", sq.num_autotopisms(&lookup)).unwrap();
+        writeln!(output()).unwrap();
+
+        writeln!(output(), "Transversals: {}", sq.num_transversals()).unwrap();
+        writeln!(
+            output(),
             "Max disjoint transversals: {}",
             sq.max_disjoint_transversals()
-        );
-        println!(
-            "Full disjoint transversal count: {}",
-            sq.full_disjoint_transversals_bitset().len()
-        );
-        println!();
+        )
+        .unwrap();
+        writeln!(output(), "Orthogonal mates: {}", sq.num_orthogonal_mates()).unwrap();
+        writeln!(output()).unwrap();
+
+        let (even, odd) = row_permutation_parities(&sq);
+        writeln!(output(), "Row permutations: {even} even, {odd} odd").unwrap();
+        writeln!(output()).unwrap();
 
-        println!("Cycles:");
+        writeln!(output(), "Cycles:").unwrap();
         for cycles in [sq.row_cycles(), sq.col_cycles(), sq.val_cycles()] {
             let mut counts: Vec<_> = {
                 let mut map = HashMap::new();
@@ -319,54 +912,90 @@ fn analyse<const N: usize>() {
             counts.sort();
 
             for (cycle, count) in counts {
-                println!("{cycle:?}: {count}");
+                writeln!(output(), "{cycle:?}: {count}").unwrap();
             }
-            println!();
+            writeln!(output()).unwrap();
         }
 
-        let (isotopy_class, perm) = sq.isotopy_class_permutations(&lookup);
-        if isotopy_class != sq {
-            println!("Isotopy class: ");
-            println!("{}", isotopy_class);
-            println!("Row permutation: {:?}", perm[0][0].as_array());
-            println!("Col permutation: {:?}", perm[0][1].as_array());
-            println!("Sym permutation: {:?}", perm[0][2].as_array());
+        if let Some((isotopy_class, perm)) = sq.isotopy_class_permutations_if_not_reduced(&lookup) {
+            writeln!(output(), "Isotopy class: ").unwrap();
+            writeln!(output(), "{}", isotopy_class).unwrap();
+            writeln!(output(), "Row permutation: {:?}", perm[0][0].as_array()).unwrap();
+            writeln!(output(), "Col permutation: {:?}", perm[0][1].as_array()).unwrap();
+            writeln!(output(), "Sym permutation: {:?}", perm[0][2].as_array()).unwrap();
 
             pretty_print_sq_n(isotopy_class);
         } else {
-            println!("Is isotopy class reduced");
+            writeln!(output(), "Is isotopy class reduced").unwrap();
         }
 
         let (main_class, rcs, perm) = sq.main_class_permutation();
         if main_class != sq {
-            println!("Main class: ");
-            println!("{}", main_class);
-            println!(
+            writeln!(output(), "Main class: ").unwrap();
+            writeln!(output(), "{}", main_class).unwrap();
+            writeln!(
+                output(),
                 "Conjugate: {}",
                 rcs.apply_array(['R', 'C', 'S'])
                     .into_iter()
                     .collect::<String>()
-            );
-            println!("Row permutation: {:?}", perm[0].as_array());
-            println!("Col permutation: {:?}", perm[1].as_array());
-            println!("Sym permutation: {:?}", perm[2].as_array());
+            )
+            .unwrap();
+            writeln!(output(), "Row permutation: {:?}", perm[0].as_array()).unwrap();
+            writeln!(output(), "Col permutation: {:?}", perm[1].as_array()).unwrap();
+            writeln!(output(), "Sym permutation: {:?}", perm[2].as_array()).unwrap();
 
             pretty_print_sq_n(main_class);
         } else {
-            println!("Is main class reduced");
+            writeln!(output(), "Is main class reduced").unwrap();
+        }
+    }
+}
+
+fn group_test<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let result = match sq.group_identity() {
+            Some(identity) if sq.is_associative() => format!("group, identity: {identity}"),
+            _ => "not a group".to_string(),
+        };
+
+        if writeln!(output(), "{result}").is_err() {
+            return;
         }
     }
 }
 
 fn generate_latin_squares(n: usize) {
     for sq in LatinSquareGeneratorDyn::new(n) {
-        println!("{sq}");
+        writeln!(output(), "{sq}").unwrap();
     }
 }
 
-fn pretty_print() {
+fn count_latin_squares(n: usize, reduced: bool) {
+    let count = if reduced {
+        LatinSquareGeneratorDyn::new_reduced(n).count()
+    } else {
+        LatinSquareGeneratorDyn::new(n).count()
+    };
+
+    writeln!(output(), "{count}").unwrap();
+}
+
+fn count_reduced_latin_squares<const N: usize>() {
+    writeln!(output(), "{}", ReducedSquareWalker::<N>::new().count()).unwrap();
+}
+
+fn pretty_print(spaced: bool) {
     while let Some(sq) = read_partial_sq_from_stdin() {
-        pretty_print_sq(sq);
+        if !spaced {
+            pretty_print_sq(sq);
+            continue;
+        }
+
+        match LatinSquareDyn::try_from(sq.clone()) {
+            Ok(sq) => writeln!(output(), "{}\n", sq.to_spaced_string()).unwrap(),
+            Err(()) => eprintln!("Not a complete latin square: {sq}"),
+        }
     }
 }
 
@@ -374,109 +1003,267 @@ fn pretty_print_sq(sq: PartialLatinSquareDyn) {
     let n = sq.n();
 
     for i in 0..n {
-        println!("+{}", "---+".repeat(n));
-        print!("|");
+        writeln!(output(), "+{}", "---+".repeat(n)).unwrap();
+        write!(output(), "|").unwrap();
         for j in 0..n {
             if let Some(value) = sq.get_partial(i, j) {
-                print!(" {} |", value);
+                write!(output(), " {} |", value).unwrap();
             } else {
-                print!("   |");
+                write!(output(), "   |").unwrap();
             }
         }
-        println!()
+        writeln!(output()).unwrap()
     }
-    println!("+{}", "---+".repeat(n));
-    println!()
+    writeln!(output(), "+{}", "---+".repeat(n)).unwrap();
+    writeln!(output()).unwrap()
 }
 
 fn pretty_print_sq_n<const N: usize>(sq: LatinSquare<N>) {
     let n = N;
 
     for i in 0..n {
-        println!("+{}", "---+".repeat(n));
-        print!("|");
+        writeln!(output(), "+{}", "---+".repeat(n)).unwrap();
+        write!(output(), "|").unwrap();
         for j in 0..n {
             let value = sq.get(i, j);
-            print!(" {} |", value);
+            write!(output(), " {} |", value).unwrap();
         }
-        println!()
+        writeln!(output()).unwrap()
     }
-    println!("+{}", "---+".repeat(n));
-    println!()
+    writeln!(output(), "+{}", "---+".repeat(n)).unwrap();
+    writeln!(output()).unwrap()
 }
 
-fn normalize_main_class<const N: usize>() {
-    let lookup = generate_minimize_rows_lookup();
+fn diff() {
+    while let Some(sq) = read_sq_from_stdin() {
+        let Some(other) = read_sq_from_stdin() else {
+            eprintln!("Missing second square");
+            return;
+        };
 
-    while let Some(sq) = read_sq_from_stdin_n::<N>() {
-        if writeln!(stdout(), "{}", sq.main_class_lookup(&lookup)).is_err() {
+        if sq.n() != other.n() {
+            eprintln!("Squares have different orders");
+            continue;
+        }
+
+        if writeln!(output(), "{}", format_diff(&sq, &other)).is_err() {
             return;
         }
     }
 }
 
+/// Formats `sq` like `pretty_print_sq`, with the cells differing from `other` replaced by `X`
+fn format_diff(sq: &LatinSquareDyn, other: &LatinSquareDyn) -> String {
+    let n = sq.n();
+    let mask = sq.difference_mask(other);
+
+    let mut result = String::new();
+    for i in 0..n {
+        result.push_str(&format!("+{}\n|", "---+".repeat(n)));
+        for j in 0..n {
+            if mask.contains(i * n + j) {
+                result.push_str(" X |");
+            } else {
+                result.push_str(&format!(" {} |", sq.get(i, j)));
+            }
+        }
+        result.push('\n');
+    }
+    result.push_str(&format!("+{}\n", "---+".repeat(n)));
+
+    result
+}
+
+fn normalize_main_class<const N: usize>(all: bool) {
+    let lookup = generate_minimize_rows_lookup();
+
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let result = if all {
+            let (main_class, rcs, perm) = sq.main_class_permutation();
+            format_main_class_permutation(&main_class, &rcs, &perm)
+        } else {
+            sq.main_class_lookup(&lookup).to_string()
+        };
+
+        if writeln!(output(), "{result}").is_err() {
+            return;
+        }
+    }
+}
+
+/// Formats the representative, the conjugate and the row, column and symbol permutations
+/// on a single line, such that
+/// `sq.permuted_rcs(rcs).permuted_rows(rows).permuted_cols(cols).permuted_vals(vals)`
+/// is the representative. The permutations are written as comma separated images
+fn format_main_class_permutation<const N: usize>(
+    main_class: &LatinSquare<N>,
+    rcs: &Permutation<3>,
+    perm: &[Permutation<N>; 3],
+) -> String {
+    let conjugate: String = rcs.apply_array(['R', 'C', 'S']).into_iter().collect();
+    let [rows, cols, vals] = perm
+        .each_ref()
+        .map(|p| p.as_array().map(|i| i.to_string()).join(","));
+
+    format!("{main_class} {conjugate} {rows} {cols} {vals}")
+}
+
 fn normalize_mols<const N: usize>() {
     let lookup = generate_minimize_rows_lookup();
     while let Some(mols) = read_mols_from_stdin::<N>() {
-        if writeln!(stdout(), "{}", mols.normalize_main_class_set(&lookup)).is_err() {
+        if writeln!(output(), "{}", mols.normalize_main_class_set(&lookup)).is_err() {
+            return;
+        }
+    }
+}
+
+fn equivalent<const N: usize>(main_class: bool) {
+    let lookup = generate_minimize_rows_lookup();
+
+    while let (Some(a), Some(b)) = (read_sq_from_stdin(), read_sq_from_stdin()) {
+        if a.n() != N || b.n() != N {
+            eprintln!("Mismatched sizes: {} and {}, expected {N}", a.n(), b.n());
+            continue;
+        }
+
+        let a: LatinSquare<N> = (&a).try_into().unwrap();
+        let b: LatinSquare<N> = (&b).try_into().unwrap();
+
+        let result = if a.isotopy_class_lookup(&lookup) == b.isotopy_class_lookup(&lookup) {
+            "isotopic"
+        } else if main_class && a.main_class_lookup(&lookup) == b.main_class_lookup(&lookup) {
+            "main-class"
+        } else {
+            "distinct"
+        };
+
+        if writeln!(output(), "{result}").is_err() {
             return;
         }
     }
 }
 
-fn generate_isotopy_classes<const N: usize>() {
+fn generate_isotopy_classes<const N: usize>(skip: Option<PathBuf>, emit_progress: bool) {
+    let skip = match skip {
+        Some(path) => match File::open(&path) {
+            Ok(file) => {
+                let mut reader = BufReader::new(file);
+                std::iter::from_fn(|| read_sq_n::<N>(&mut reader)).collect()
+            }
+            Err(err) => {
+                eprintln!("Could not open {}: {err}", path.display());
+                return;
+            }
+        },
+        None => HashSet::new(),
+    };
+
+    let _ = write_isotopy_classes(&mut output(), &skip, emit_progress);
+}
+
+fn write_isotopy_classes<const N: usize>(
+    writer: &mut impl Write,
+    skip: &HashSet<LatinSquare<N>>,
+    emit_progress: bool,
+) -> std::io::Result<()> {
     let lookup = generate_minimize_rows_lookup_simd::<N>();
+
+    let mut emitted = 0;
+    let mut skipped = 0;
     for sq in IsotopyClassGenerator::<N>::new(&lookup) {
-        if writeln!(stdout(), "{sq}").is_err() {
-            return;
+        if skip.contains(&sq) {
+            skipped += 1;
+        } else {
+            writeln!(writer, "{sq}")?;
+            emitted += 1;
         }
+
+        if emit_progress && (emitted + skipped) % 1000 == 0 {
+            progress(format_args!("emitted: {emitted}, skipped: {skipped}"));
+        }
+    }
+
+    if emit_progress {
+        progress(format_args!("emitted: {emitted}, skipped: {skipped}"));
+    }
+
+    Ok(())
+}
+
+fn generate_main_classes<const N: usize>(max_threads: usize, verify: bool) {
+    let lookup = generate_minimize_rows_lookup_simd::<N>();
+
+    let generator = ThreadedMainClassGenerator::<N>::new(&lookup);
+    if verify {
+        generator.run_verified(max_threads);
+    } else {
+        generator.run(max_threads);
     }
 }
 
-fn generate_main_classes<const N: usize>(max_threads: usize) {
+fn count_main_classes<const N: usize>(max_threads: usize) {
     let lookup = generate_minimize_rows_lookup_simd::<N>();
 
-    ThreadedMainClassGenerator::<N>::new(&lookup).run(max_threads);
+    let counts = ThreadedMainClassGenerator::<N>::new(&lookup).count(max_threads);
+
+    writeln!(output(), "Main classes: {}", counts.values().sum::<usize>()).unwrap();
+    for (intercalates, count) in counts {
+        writeln!(output(), "{intercalates} intercalates: {count}").unwrap();
+    }
+}
+
+fn check_critical_set() {
+    while let Some(sq) = read_sq_from_stdin() {
+        let Some(partial_sq) = read_partial_sq_from_stdin() else {
+            eprintln!("Missing partial square");
+            return;
+        };
+
+        if partial_sq.n() != sq.n() {
+            eprintln!("Squares have different orders");
+            continue;
+        }
+
+        if writeln!(output(), "{}", critical_set_status(&sq, &partial_sq)).is_err() {
+            return;
+        }
+    }
+}
+
+fn critical_set_status(sq: &LatinSquareDyn, partial_sq: &PartialLatinSquareDyn) -> &'static str {
+    if partial_sq.is_critical_set_of(sq) {
+        "critical"
+    } else if partial_sq.is_uniquely_completable_to(sq) {
+        "uniquely completable but not minimal"
+    } else {
+        "not uniquely completable"
+    }
 }
 
-fn find_scs(reverse: bool) {
+fn find_scs(reverse: bool, all: bool) {
     while let Some(sq) = read_sq_from_stdin() {
         let differences = sq.differences();
-        dbg!(differences.len());
+        progress(format_args!(
+            "n = {}: {} differences",
+            sq.n(),
+            differences.len()
+        ));
 
         let start = sq.n() - 1;
         let end = sq.n().pow(2) - 1;
 
         if !reverse {
-            for i in start..=end {
-                dbg!(i);
-                let hitting_sets = MMCSHittingSetGenerator::new(differences.clone(), i);
-
-                let mut found = false;
-                'h: for hitting_set in hitting_sets {
-                    let partial_sq = sq.mask(hitting_set);
+            let critical_sets = smallest_critical_sets(&sq, differences, all);
 
-                    for partial_sq in
-                        PartialSquareGeneratorDyn::new_partial(sq.clone(), partial_sq.clone(), i)
-                    {
-                        if partial_sq.is_uniquely_completable_to(&sq) {
-                            found = true;
-                            println!("{sq}");
-                            println!("{partial_sq}");
-                            break 'h;
-                        }
-                    }
-                }
-
-                if found {
-                    break;
-                }
+            writeln!(output(), "{sq}").unwrap();
+            for partial_sq in critical_sets {
+                writeln!(output(), "{partial_sq}").unwrap();
             }
         } else {
             let mut hitting_sets = MMCSHittingSetGenerator::new(differences, end);
             let mut scs = PartialLatinSquareDyn::empty(sq.n());
             for i in (start..=end).rev() {
-                dbg!(i);
+                progress(format_args!("n = {}: searching {i} entries", sq.n()));
 
                 let mut found = false;
                 'h: for hitting_set in hitting_sets.by_ref() {
@@ -488,7 +1275,7 @@ fn find_scs(reverse: bool) {
                         if partial_sq.is_uniquely_completable_to(&sq) {
                             found = true;
                             scs = partial_sq;
-                            dbg!(scs.to_string());
+                            progress(format_args!("n = {}: found {scs}", sq.n()));
                             break 'h;
                         }
                     }
@@ -496,21 +1283,58 @@ fn find_scs(reverse: bool) {
                 hitting_sets.decrease_max_entries();
 
                 if !found || i == start {
-                    println!("{sq}");
-                    println!("{scs}");
+                    writeln!(output(), "{sq}").unwrap();
+                    writeln!(output(), "{scs}").unwrap();
                     break;
                 }
             }
         }
-        println!();
+        writeln!(output()).unwrap();
+    }
+}
+
+/// Returns the critical sets of the smallest size, or only the first one found if `all` is false
+fn smallest_critical_sets(
+    sq: &LatinSquareDyn,
+    differences: Vec<BitSet128>,
+    all: bool,
+) -> Vec<PartialLatinSquareDyn> {
+    let start = sq.n() - 1;
+    let end = sq.n().pow(2) - 1;
+
+    let mut critical_sets = Vec::new();
+
+    for i in start..=end {
+        progress(format_args!("n = {}: searching {i} entries", sq.n()));
+        let hitting_sets = MMCSHittingSetGenerator::new(differences.clone(), i);
+
+        'h: for hitting_set in hitting_sets {
+            let partial_sq = sq.mask(hitting_set);
+
+            for partial_sq in PartialSquareGeneratorDyn::new_partial(sq.clone(), partial_sq, i) {
+                if !critical_sets.contains(&partial_sq) && partial_sq.is_uniquely_completable_to(sq)
+                {
+                    critical_sets.push(partial_sq);
+                    if !all {
+                        break 'h;
+                    }
+                }
+            }
+        }
+
+        if !critical_sets.is_empty() {
+            break;
+        }
     }
+
+    critical_sets
 }
 
-fn find_lcs(max_threads: usize) {
+fn find_lcs(max_threads: usize, max_size: Option<usize>) {
     let mut threads = Vec::new();
 
     while let Some(sq) = read_sq_from_stdin() {
-        let thread = thread::spawn(move || find_lcs_sq(sq));
+        let thread = thread::spawn(move || find_lcs_sq(sq, max_size));
 
         threads.push(thread);
 
@@ -533,10 +1357,23 @@ fn find_lcs(max_threads: usize) {
     }
 }
 
-fn find_lcs_sq(sq: LatinSquareDyn) {
+fn find_lcs_sq(sq: LatinSquareDyn, max_size: Option<usize>) {
+    let all_lcs = largest_critical_sets(&sq, max_size.unwrap_or(sq.n() * sq.n()));
+
+    let mut stdout = output();
+
+    writeln!(stdout, "{}", sq).unwrap();
+    for lcs in all_lcs {
+        writeln!(stdout, "{lcs}").unwrap();
+    }
+    writeln!(stdout,).unwrap();
+}
+
+/// Returns the largest critical sets with at most `max_size` entries
+fn largest_critical_sets(sq: &LatinSquareDyn, max_size: usize) -> Vec<PartialLatinSquareDyn> {
     let differences = sq.differences();
 
-    let hitting_sets = MMCSHittingSetGenerator::new(differences, sq.n() * sq.n());
+    let hitting_sets = MMCSHittingSetGenerator::new(differences, max_size);
 
     let mut lcs = PartialLatinSquareDyn::empty(sq.n());
     let mut all_lcs = Vec::new();
@@ -544,20 +1381,23 @@ fn find_lcs_sq(sq: LatinSquareDyn) {
     for hitting_set in hitting_sets {
         let partial_sq = sq.mask(hitting_set);
 
-        if !partial_sq.is_critical_set_of(&sq) {
+        if !partial_sq.is_critical_set_of(sq) {
             let num_entries = partial_sq.num_entries();
 
             'l: loop {
-                for partial_sq in PartialSquareGeneratorDyn::new_partial(
-                    sq.clone(),
-                    partial_sq.clone(),
-                    (lcs.num_entries() + 1).max(num_entries + 1),
-                )
-                .filter(|s| s.is_critical_set_of(&sq))
+                let size = (lcs.num_entries() + 1).max(num_entries + 1);
+                if size > max_size {
+                    break;
+                }
+
+                for partial_sq in
+                    PartialSquareGeneratorDyn::new_partial(sq.clone(), partial_sq.clone(), size)
+                        .filter(|s| s.is_critical_set_of(sq))
                 {
                     if lcs.num_entries() < partial_sq.num_entries() {
                         lcs = partial_sq.clone();
                         all_lcs = vec![partial_sq];
+                        progress(format_args!("n = {}: found {lcs}", sq.n()));
                         continue 'l;
                     } else if lcs.num_entries() == partial_sq.num_entries() {
                         all_lcs.push(partial_sq);
@@ -569,67 +1409,100 @@ fn find_lcs_sq(sq: LatinSquareDyn) {
             if lcs.num_entries() < partial_sq.num_entries() {
                 lcs = partial_sq.clone();
                 all_lcs = vec![partial_sq];
+                progress(format_args!("n = {}: found {lcs}", sq.n()));
             } else if lcs.num_entries() == partial_sq.num_entries() {
                 all_lcs.push(partial_sq);
             }
         }
     }
 
-    let mut stdout = stdout().lock();
-
-    writeln!(stdout, "{}", sq).unwrap();
-    for lcs in all_lcs {
-        writeln!(stdout, "{lcs}").unwrap();
-    }
-    writeln!(stdout,).unwrap();
+    all_lcs
 }
 
-fn find_all_cs() {
+fn find_all_cs(text: bool) {
     while let Some(sq) = read_sq_from_stdin() {
-        let mut differences = sq.differences();
-        dbg!(differences.len());
+        let critical_sets = critical_sets(&sq);
 
-        let hitting_sets = MMCSHittingSetGenerator::new(differences.clone(), sq.n() * sq.n());
+        let mut stdout = output();
 
-        for hitting_set in hitting_sets {
-            let partial_sq = sq.mask(hitting_set);
+        if text {
+            if write_critical_sets_text(&sq, critical_sets, &mut stdout).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        for set in critical_sets {
+            let partial_sq = sq.mask(set);
 
             if !partial_sq.is_critical_set_of(&sq) {
-                // dbg!(&partial_sq);
-                for solution in LatinSquareGeneratorDyn::from_partial_sq(&partial_sq) {
-                    let difference = sq.difference_mask(&solution);
+                unreachable!("{partial_sq} is not a critical set of {sq}");
+            }
 
-                    if !difference.is_empty()
-                        && !differences.iter().any(|s| s.is_subset_of(difference))
-                    {
-                        differences.retain(|s| !difference.is_subset_of(*s));
-                        differences.push(difference);
-                        dbg!(differences.len());
-                    }
-                }
+            if stdout.write_all(&partial_sq.to_mask_bytes()).is_err() {
+                return;
             }
         }
-        dbg!(differences.len());
+    }
+}
 
-        let critical_sets = MMCSHittingSetGenerator::new(differences.clone(), sq.n() * sq.n());
+/// Writes the square and then each critical set as a partial square, one per line
+fn write_critical_sets_text(
+    sq: &LatinSquareDyn,
+    critical_sets: impl Iterator<Item = BitSet128>,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    writeln!(writer, "{sq}")?;
+    for set in critical_sets {
+        let partial_sq = sq.mask(set);
+
+        if !partial_sq.is_critical_set_of(sq) {
+            unreachable!("{partial_sq} is not a critical set of {sq}");
+        }
 
-        let bytes_needed = (sq.n() * sq.n()).div_ceil(8);
+        writeln!(writer, "{partial_sq}")?;
+    }
+    writeln!(writer)
+}
 
-        let mut stdout = stdout();
+/// Returns a generator for all critical sets of `sq`
+fn critical_sets(sq: &LatinSquareDyn) -> MMCSHittingSetGenerator {
+    let mut differences = sq.differences();
+    progress(format_args!(
+        "n = {}: {} differences",
+        sq.n(),
+        differences.len()
+    ));
 
-        for set in critical_sets {
-            let partial_sq = sq.mask(set);
+    let hitting_sets = MMCSHittingSetGenerator::new(differences.clone(), sq.n() * sq.n());
 
-            if !partial_sq.is_critical_set_of(&sq) {
-                dbg!(partial_sq);
-                unreachable!();
-            }
+    for hitting_set in hitting_sets {
+        let partial_sq = sq.mask(hitting_set);
 
-            stdout
-                .write_all(&set.bits().to_le_bytes()[0..bytes_needed])
-                .unwrap();
+        if !partial_sq.is_critical_set_of(sq) {
+            for solution in LatinSquareGeneratorDyn::from_partial_sq(&partial_sq) {
+                let difference = sq.difference_mask(&solution);
+
+                if !difference.is_empty() && !differences.iter().any(|s| s.is_subset_of(difference))
+                {
+                    differences.retain(|s| !difference.is_subset_of(*s));
+                    differences.push(difference);
+                    progress(format_args!(
+                        "n = {}: {} differences",
+                        sq.n(),
+                        differences.len()
+                    ));
+                }
+            }
         }
     }
+    progress(format_args!(
+        "n = {}: {} differences in total",
+        sq.n(),
+        differences.len()
+    ));
+
+    MMCSHittingSetGenerator::new(differences, sq.n() * sq.n())
 }
 
 fn find_all_uc(brute_force: bool) {
@@ -644,7 +1517,7 @@ fn find_all_uc(brute_force: bool) {
                 let partial_sq = sq.mask(mask);
 
                 if partial_sq.is_uniquely_completable() {
-                    println!("{partial_sq}");
+                    writeln!(output(), "{partial_sq}").unwrap();
                 }
             }
         }
@@ -661,7 +1534,7 @@ fn find_all_uc(brute_force: bool) {
             all_union = sq.union(&all_union);
         }
 
-        dbg!(&all_union);
+        progress(format_args!("union of all sets: {all_union}"));
 
         let mut total = 0;
 
@@ -692,7 +1565,12 @@ fn find_all_uc(brute_force: bool) {
                 }
             }
 
-            dbg!(&new_unions.len());
+            progress(format_args!(
+                "n = {}: {} unions of {} sets",
+                all_union.n(),
+                new_unions.len(),
+                i + 1
+            ));
             if new_unions.len() == 0 {
                 break;
             }
@@ -700,7 +1578,7 @@ fn find_all_uc(brute_force: bool) {
             unions = new_unions;
         }
 
-        dbg!(total);
+        progress(format_args!("total: {total}"));
     }
 }
 
@@ -712,37 +1590,110 @@ fn decode_cs() {
 
     let bytes_needed = (sq.n() * sq.n()).div_ceil(8);
 
-    let mut stdin = stdin();
+    let mut stdin = input();
 
-    let mut buffer = [0; 16];
+    let mut buffer = vec![0; bytes_needed];
 
-    while stdin.read_exact(&mut buffer[0..bytes_needed]).is_ok() {
-        let bitset = BitSet128::from_bits(u128::from_le_bytes(buffer));
+    while stdin.read_exact(&mut buffer).is_ok() {
+        let partial_sq = PartialLatinSquareDyn::from_mask_bytes(&sq, &buffer);
 
-        let partial_sq = sq.mask(bitset);
-
-        println!("{partial_sq}");
+        writeln!(output(), "{partial_sq}").unwrap();
     }
 }
 
-fn find_mols<const N: usize>(mols: usize) {
+fn find_mols<const N: usize>(mols: usize, oa: bool) {
     let lookup = generate_minimize_rows_lookup();
 
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
         let mols = sq.kmols(mols, lookup.as_slice());
-        let mut stdout = stdout().lock();
+        let mut stdout = output();
         for mols in mols {
-            writeln!(stdout, "{mols}").unwrap();
+            if oa {
+                writeln!(stdout, "{}", mols.to_orthogonal_array()).unwrap();
+            } else {
+                writeln!(stdout, "{mols}").unwrap();
+            }
+        }
+    }
+}
+
+fn extend_mols<const N: usize>(target: usize) {
+    while let Some(mols) = read_mols_from_stdin::<N>() {
+        let extensions = LatinSquare::extend_mols(mols.squares(), target);
+
+        let mut stdout = output();
+        if extensions.is_empty() && writeln!(stdout, "No extension to {target} squares").is_err() {
+            return;
+        }
+        for extension in extensions {
+            if writeln!(stdout, "{extension}").is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn orthogonality_graph<const N: usize>() {
+    let sqs: Vec<_> = std::iter::from_fn(read_sq_from_stdin_n::<N>).collect();
+
+    let mut stdout = output();
+    for (i, neighbours) in orthogonality_adjacency(&sqs).into_iter().enumerate() {
+        let neighbours: Vec<_> = neighbours.iter().map(|j| j.to_string()).collect();
+        if writeln!(stdout, "{i}: {}", neighbours.join(" ")).is_err() {
+            return;
+        }
+    }
+}
+
+/// Returns for each square the indices of the squares it is orthogonal to
+fn orthogonality_adjacency<const N: usize>(sqs: &[LatinSquare<N>]) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::new(); sqs.len()];
+
+    for i in 0..sqs.len() {
+        for j in i + 1..sqs.len() {
+            if sqs[i].is_orthogonal_to(&sqs[j]) {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+            }
+        }
+    }
+
+    for neighbours in &mut adjacency {
+        neighbours.sort();
+    }
+
+    adjacency
+}
+
+fn greedy_mols<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let mols = sq.greedy_mols();
+
+        let mut stdout = output();
+        if writeln!(stdout, "{}", mols.len()).is_err() {
+            return;
+        }
+        for sq in mols {
+            if writeln!(stdout, "{sq}").is_err() {
+                return;
+            }
+        }
+        if writeln!(stdout).is_err() {
+            return;
         }
     }
 }
 
-fn find_all_mols<const N: usize>(max_threads: usize, buffer_size: usize) {
+fn find_all_mols<const N: usize>(
+    max_threads: usize,
+    buffer_size: usize,
+    timeout_secs: Option<u64>,
+) {
     let lookup = Arc::new(generate_minimize_rows_lookup());
 
     if max_threads == 1 {
         while let Some(sq) = read_sq_from_stdin_n() {
-            find_all_mols_for_sq(sq, lookup.clone());
+            find_all_mols_for_sq(sq, lookup.clone(), timeout_secs);
         }
         return;
     }
@@ -763,7 +1714,7 @@ fn find_all_mols<const N: usize>(max_threads: usize, buffer_size: usize) {
 
         let thread = thread::spawn(move || {
             for sq in move_buffer {
-                find_all_mols_for_sq(sq, lookup.clone())
+                find_all_mols_for_sq(sq, lookup.clone(), timeout_secs)
             }
         });
 
@@ -787,7 +1738,7 @@ fn find_all_mols<const N: usize>(max_threads: usize, buffer_size: usize) {
 
     let thread = thread::spawn(move || {
         for sq in move_buffer {
-            find_all_mols_for_sq(sq, lookup.clone())
+            find_all_mols_for_sq(sq, lookup.clone(), timeout_secs)
         }
     });
 
@@ -801,9 +1752,11 @@ fn find_all_mols<const N: usize>(max_threads: usize, buffer_size: usize) {
 fn find_all_mols_for_sq<const N: usize>(
     sq: LatinSquare<N>,
     lookup: Arc<Vec<Vec<(Permutation<N>, Permutation<N>)>>>,
+    timeout_secs: Option<u64>,
 ) {
-    let mols = sq.mols(lookup.as_slice());
-    let mut stdout = stdout().lock();
+    let deadline = timeout_secs.map(|secs| Instant::now() + Duration::from_secs(secs));
+    let mols = sq.mols_with_deadline(lookup.as_slice(), deadline);
+    let mut stdout = output();
     for mols in mols {
         writeln!(stdout, "{mols}").unwrap();
     }
@@ -814,7 +1767,71 @@ fn solve() {
         let solutions = LatinSquareGeneratorDyn::from_partial_sq(&sq);
 
         for solution in solutions {
-            println!("{}", solution);
+            writeln!(output(), "{}", solution).unwrap();
+        }
+    }
+}
+
+fn count_completions(limit: Option<usize>) {
+    while let Some(sq) = read_partial_sq_from_stdin() {
+        let count = if sq.is_valid() {
+            sq.num_completions_up_to(limit.unwrap_or(usize::MAX))
+        } else {
+            0
+        };
+
+        let result = match limit {
+            Some(limit) if count == limit => format!(">={limit}"),
+            _ => count.to_string(),
+        };
+
+        if writeln!(output(), "{result}").is_err() {
+            return;
+        }
+    }
+}
+
+fn difficulty() {
+    while let Some(sq) = read_partial_sq_from_stdin() {
+        if writeln!(output(), "{}", format_difficulty(&sq)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Formats the candidate counts of `sq` as `total row,col:candidates`, or `total` if `sq` is full
+fn format_difficulty(sq: &PartialLatinSquareDyn) -> String {
+    let constraints = ConstraintsDyn::new_partial(sq);
+    let total = constraints.total_candidates();
+
+    match constraints.most_constrained_cell() {
+        Some((i, j, candidates)) => format!("{total} {i},{j}:{candidates}"),
+        None => total.to_string(),
+    }
+}
+
+fn solve_one() {
+    while let Some(sq) = read_partial_sq_from_stdin() {
+        let result = match sq.first_completion() {
+            Some(solution) => solution.to_string(),
+            None => "UNSAT".to_string(),
+        };
+
+        if writeln!(output(), "{result}").is_err() {
+            return;
+        }
+    }
+}
+
+fn complete_rectangle() {
+    while let Some(sq) = read_partial_sq_from_stdin() {
+        let Some(completion) = sq.complete_rectangle() else {
+            eprintln!("Not a latin rectangle: {sq}");
+            continue;
+        };
+
+        if writeln!(output(), "{completion}").is_err() {
+            return;
         }
     }
 }
@@ -830,11 +1847,11 @@ fn count_entries() {
         let num_entries = sq.num_entries();
         counts[num_entries] += 1;
 
-        println!("{sq}");
+        writeln!(output(), "{sq}").unwrap();
     }
 
     for (num_entries, count) in counts.into_iter().enumerate() {
-        println!("{num_entries}: {count}");
+        writeln!(output(), "{num_entries}: {count}").unwrap();
     }
 }
 
@@ -847,7 +1864,7 @@ fn count_isotopy_classes<const N: usize>(max_threads: usize, buffer_size: usize)
             total += sq.num_isotopy_classes(&lookup);
         }
 
-        println!("{total}");
+        writeln!(output(), "{total}").unwrap();
     } else {
         let lookup = Arc::new(generate_minimize_rows_lookup());
         let mut threads = Vec::new();
@@ -906,7 +1923,7 @@ fn count_isotopy_classes<const N: usize>(max_threads: usize, buffer_size: usize)
             total += thread.join().unwrap();
         }
 
-        println!("{}", total);
+        writeln!(output(), "{}", total).unwrap();
     }
 }
 
@@ -914,12 +1931,12 @@ fn transversals<const N: usize>() {
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
         let transversals = sq.transversals_bitset();
 
-        println!("{sq}");
+        writeln!(output(), "{sq}").unwrap();
         for transversal in transversals.into_iter().map(|t| sq.mask(t)) {
-            println!("{transversal}")
+            writeln!(output(), "{transversal}").unwrap()
         }
 
-        println!()
+        writeln!(output()).unwrap()
     }
 }
 
@@ -927,69 +1944,141 @@ fn sub_transversals<const N: usize>(k: usize) {
     assert!(k <= N);
 
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
-        let subsquares = sq.subsquares_bitset(k);
-        let transversals = sq.transversals_bitset();
-
-        let mut subtransversals_per_subsq = Vec::new();
-
-        for subsquare in &subsquares {
-            let mut subtransversals = Vec::new();
-
-            for transversal in &transversals {
-                if subsquare.intersect(*transversal).len() == k {
-                    subtransversals.push(sq.mask(*transversal));
-                }
-            }
-
-            if !subtransversals.is_empty() {
-                subtransversals_per_subsq.push((subsquare, subtransversals));
-            }
-        }
+        let subtransversals_per_subsq = subtransversals(&sq, k);
 
         if !subtransversals_per_subsq.is_empty() {
-            println!("{sq}");
+            writeln!(output(), "{sq}").unwrap();
             for (subsq, subtransversals) in subtransversals_per_subsq {
-                println!("{}", sq.mask(*subsq));
+                writeln!(output(), "{}", sq.mask(subsq)).unwrap();
 
                 for subtransversal in subtransversals {
-                    println!("{subtransversal}")
+                    writeln!(output(), "{}", sq.mask(subtransversal)).unwrap()
                 }
 
-                println!()
+                writeln!(output()).unwrap()
             }
-            println!()
+            writeln!(output()).unwrap()
         }
     }
 }
 
-fn main_class_size<const N: usize>() {
-    let lookup = generate_minimize_rows_lookup();
-    let max = 6 * (factorial(N) as u128).pow(3);
+fn count_sub_transversals<const N: usize>(k: usize) {
+    assert!(k <= N);
 
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
-        let vec = &sq.main_class_permutations(&lookup).1;
-        let count = vec.len() as u128 - 1;
-
-        assert_eq!(max % count, 0);
+        let (subsquares, total) = count_subtransversals(&sq, k);
 
-        println!("{}", max / count);
+        if writeln!(output(), "{subsquares} {total}").is_err() {
+            return;
+        }
     }
 }
 
-fn expand<const N: usize>() {
-    let lookup = generate_minimize_rows_lookup();
+/// Returns the k-subsquares of `sq` which contain a sub-transversal,
+/// together with the transversals of `sq` that intersect them in k cells
+fn subtransversals<const N: usize>(
+    sq: &LatinSquare<N>,
+    k: usize,
+) -> Vec<(BitSet128, Vec<BitSet128>)> {
+    let subsquares = sq.subsquares_bitset(k);
+    let transversals = sq.transversals_bitset();
 
-    // let mut last_layer = HashSet::new();
-    // let mut next_layer = HashSet::new();
-    // let mut queue = HashSet::new();
+    let mut subtransversals_per_subsq = Vec::new();
 
-    // while let Some(sq) = read_sq_from_stdin_n::<N>() {
-    //     queue.insert(sq);
+    for subsquare in subsquares {
+        let mut subtransversals = Vec::new();
+
+        for transversal in &transversals {
+            if subsquare.intersect(*transversal).len() == k {
+                subtransversals.push(*transversal);
+            }
+        }
+
+        if !subtransversals.is_empty() {
+            subtransversals_per_subsq.push((subsquare, subtransversals));
+        }
+    }
+
+    subtransversals_per_subsq
+}
+
+/// Returns the number of k-subsquares with a sub-transversal and the total number of sub-transversals
+fn count_subtransversals<const N: usize>(sq: &LatinSquare<N>, k: usize) -> (usize, usize) {
+    let subtransversals = subtransversals(sq, k);
+    let total = subtransversals.iter().map(|(_, t)| t.len()).sum();
+
+    (subtransversals.len(), total)
+}
+
+fn main_class_size<const N: usize>() {
+    let lookup = generate_minimize_rows_lookup();
+    let max = 6 * (factorial(N) as u128).pow(3);
+
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let vec = &sq.main_class_permutations(&lookup).1;
+        let count = vec.len() as u128 - 1;
+
+        assert_eq!(max % count, 0);
+
+        writeln!(output(), "{}", max / count).unwrap();
+    }
+}
+
+fn canonicalize<const N: usize>() {
+    let lookup = generate_minimize_rows_lookup();
+
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let hash = sq.main_class_hash(&lookup);
+        let main_class = sq.main_class_lookup(&lookup);
+
+        if writeln!(output(), "{hash:016x} {main_class}").is_err() {
+            return;
+        }
+    }
+}
+
+fn dedup_main_class<const N: usize>() {
+    let lookup = generate_minimize_rows_lookup();
+
+    let counts = main_class_counts(std::iter::from_fn(read_sq_from_stdin_n::<N>), &lookup);
+
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort();
+
+    for (sq, count) in counts {
+        if writeln!(output(), "{sq} {count}").is_err() {
+            return;
+        }
+    }
+}
+
+fn main_class_counts<const N: usize>(
+    sqs: impl Iterator<Item = LatinSquare<N>>,
+    lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
+) -> HashMap<LatinSquare<N>, usize> {
+    let mut counts = HashMap::new();
+
+    for sq in sqs {
+        *counts.entry(sq.main_class_lookup(lookup)).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+fn expand<const N: usize>(cache: bool) {
+    let lookup = generate_minimize_rows_lookup();
+
+    // let mut last_layer = HashSet::new();
+    // let mut next_layer = HashSet::new();
+    // let mut queue = HashSet::new();
+
+    // while let Some(sq) = read_sq_from_stdin_n::<N>() {
+    //     queue.insert(sq);
     // }
 
     // while !queue.is_empty() {
     //     for sq in queue.iter() {
-    //         println!("{sq}");
+    //         writeln!(output(), "{sq}").unwrap();
     //         for mate in sq
     //             .orthogonal_squares()
     //             .map(|sq| sq.main_class_lookup(&lookup))
@@ -1002,105 +2091,234 @@ fn expand<const N: usize>() {
     //     std::mem::swap(&mut next_layer, &mut queue);
     // }
 
+    let sqs = std::iter::from_fn(read_sq_from_stdin_n::<N>);
+
+    expand_main_classes(sqs, &lookup, cache, |sq| {
+        writeln!(output(), "{sq}").unwrap()
+    });
+}
+
+/// Visits the main classes of `sqs` and all main classes reachable from them by taking orthogonal mates.
+/// Returns the set of all main classes found.
+/// With `cache`, the mates of each main class are only computed once, even if it appears multiple times in `sqs`
+fn expand_main_classes<const N: usize>(
+    sqs: impl Iterator<Item = LatinSquare<N>>,
+    lookup: &[Vec<(Permutation<N>, Permutation<N>)>],
+    cache: bool,
+    mut visit: impl FnMut(&LatinSquare<N>),
+) -> HashSet<LatinSquare<N>> {
     let mut queue = BinaryHeap::new();
     let mut found = HashSet::new();
+    let mut mates_cache: HashMap<LatinSquare<N>, Vec<LatinSquare<N>>> = HashMap::new();
+    let mut transversals_cache = HashMap::new();
 
-    while let Some(sq) = read_sq_from_stdin_n::<N>() {
-        let sq = sq.main_class_lookup(&lookup);
+    for sq in sqs {
+        let sq = sq.main_class_lookup(lookup);
         found.insert(sq);
-        queue.push((sq.num_transversals(), sq));
+        queue.push((sq.num_transversals_cached(&mut transversals_cache), sq));
     }
 
     while let Some((t, sq)) = queue.pop() {
-        dbg!(t, queue.len(), found.len());
-        println!("{sq}");
+        progress(format_args!(
+            "n = {N}: {t} transversals, {} queued, {} found",
+            queue.len(),
+            found.len()
+        ));
+        visit(&sq);
+
+        let main_class_mates = |sq: &LatinSquare<N>| {
+            let mut mates: Vec<_> = sq
+                .orthogonal_squares()
+                .map(|s| s.main_class_lookup(lookup))
+                .collect();
+            mates.sort();
+            mates.dedup();
+            mates
+        };
 
-        let mut mates: Vec<_> = sq
-            .orthogonal_squares()
-            .map(|s| s.main_class_lookup(&lookup))
-            .collect();
-        mates.sort();
-        mates.dedup();
+        let mates = if cache {
+            mates_cache
+                .entry(sq)
+                .or_insert_with(|| main_class_mates(&sq))
+                .clone()
+        } else {
+            main_class_mates(&sq)
+        };
 
         for mate in mates {
             if found.insert(mate) {
-                queue.push((mate.num_transversals(), mate));
+                queue.push((mate.num_transversals_cached(&mut transversals_cache), mate));
             }
         }
     }
+
+    found
 }
 
-fn count_transversals<const N: usize>() {
-    while let Some(sq) = read_sq_from_stdin_n::<N>() {
-        println!("{}", sq.num_transversals());
+fn count_transversals<const N: usize>(max_threads: usize) {
+    let _ = write_transversal_counts::<N>(&mut input(), &mut output(), max_threads);
+}
+
+fn write_transversal_counts<const N: usize>(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    max_threads: usize,
+) -> std::io::Result<()> {
+    while let Some(sq) = read_sq_n::<N>(reader) {
+        writeln!(writer, "{}", sq.num_transversals_threaded(max_threads))?;
     }
+
+    Ok(())
 }
 
-fn shuffle(seed: u64, rows: bool, cols: bool, vals: bool) {
-    fn xoshiro(state: &mut [u64; 4]) -> u64 {
-        let result = state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+fn transversal_histogram<const N: usize>() {
+    let counts = transversal_counts(std::iter::from_fn(read_sq_from_stdin_n::<N>));
 
-        let new_state = [
-            state[0] ^ state[1] ^ state[3],
-            state[0] ^ state[1] ^ state[2],
-            state[2] ^ state[0] ^ (state[1] << 17),
-            (state[3] ^ state[1]).rotate_left(45),
-        ];
+    for (num_transversals, count) in counts.into_iter().enumerate() {
+        if count != 0 {
+            writeln!(output(), "{num_transversals}: {count}").unwrap();
+        }
+    }
+}
 
-        *state = new_state;
-        result
+/// Returns the number of squares with `i` transversals at index `i`
+fn transversal_counts<const N: usize>(sqs: impl Iterator<Item = LatinSquare<N>>) -> Vec<usize> {
+    let mut counts = Vec::new();
+
+    for sq in sqs {
+        let num_transversals = sq.num_transversals();
+        if num_transversals >= counts.len() {
+            counts.resize(num_transversals + 1, 0);
+        }
+
+        counts[num_transversals] += 1;
+    }
+
+    counts
+}
+
+fn apply(rows: Option<String>, cols: Option<String>, vals: Option<String>) {
+    let mut permutations = [None, None, None];
+    for (permutation, arg) in permutations.iter_mut().zip([rows, cols, vals]) {
+        if let Some(arg) = arg {
+            match PermutationDyn::try_from(arg.as_str()) {
+                Ok(value) => *permutation = Some(value),
+                Err(err) => {
+                    eprintln!("{err}");
+                    return;
+                }
+            }
+        }
+    }
+
+    while let Some(mut sq) = read_partial_sq_from_stdin() {
+        if let Err(err) = apply_permutations(&mut sq, &permutations) {
+            eprintln!("{err}");
+            continue;
+        }
+
+        if writeln!(output(), "{sq}").is_err() {
+            return;
+        }
+    }
+}
+
+/// Applies the row, column and symbol permutations which are given
+fn apply_permutations(
+    sq: &mut PartialLatinSquareDyn,
+    permutations: &[Option<PermutationDyn>; 3],
+) -> Result<(), String> {
+    let n = sq.n();
+    if let Some(permutation) = permutations.iter().flatten().find(|p| p.len() != n) {
+        return Err(format!(
+            "Permutation of length {} does not match the order {n}",
+            permutation.len()
+        ));
+    }
+
+    let [rows, cols, vals] = permutations;
+    if let Some(rows) = rows {
+        sq.permute_rows(rows);
+    }
+    if let Some(cols) = cols {
+        sq.permute_cols(cols);
     }
+    if let Some(vals) = vals {
+        sq.permute_vals(vals);
+    }
+
+    Ok(())
+}
 
+fn shuffle(seed: u64, rows: bool, cols: bool, vals: bool, log: bool, transpose: bool) {
     let mut state = [seed, 2, 3, 4];
+    let mut rng = || {
+        let (result, next_state) = RandomLatinSquareGeneratorDyn::xoshiro(state);
+        state = next_state;
+        result
+    };
 
     for _ in 0..100 {
-        xoshiro(&mut state);
+        rng();
     }
 
     while let Some(mut sq) = read_partial_sq_from_stdin() {
         let n = sq.n();
+        let mut ranks = [0; 3];
 
         if rows {
-            let rank = xoshiro(&mut state) as usize % factorial(n);
-            let permutations = PermutationDyn::from_rank(rank, n);
+            let permutation = PermutationDyn::random(n, &mut rng);
+            ranks[0] = permutation.to_rank();
 
-            sq.permute_rows(&permutations);
+            sq.permute_rows(&permutation);
         }
 
         if cols {
-            let rank = xoshiro(&mut state) as usize % factorial(n);
-            let permutations = PermutationDyn::from_rank(rank, n);
+            let permutation = PermutationDyn::random(n, &mut rng);
+            ranks[1] = permutation.to_rank();
 
-            sq.permute_cols(&permutations);
+            sq.permute_cols(&permutation);
         }
 
         if vals {
-            let rank = xoshiro(&mut state) as usize % factorial(n);
-            let permutations = PermutationDyn::from_rank(rank, n);
+            let permutation = PermutationDyn::random(n, &mut rng);
+            ranks[2] = permutation.to_rank();
+
+            sq.permute_vals(&permutation);
+        }
 
-            sq.permute_vals(&permutations);
+        if transpose {
+            sq = sq.transpose();
         }
 
-        println!("{sq}");
+        if log {
+            eprintln!("{} {} {}", ranks[0], ranks[1], ranks[2]);
+        }
+        writeln!(output(), "{sq}").unwrap();
     }
 }
 
-fn to_tex(standalone: bool) {
+fn to_tex(standalone: bool, transpose: bool) {
     if standalone {
-        println!(
+        writeln!(
+            output(),
             "\\documentclass[preview]{{standalone}}
 \\usepackage{{tikz}}
 \\newcounter{{row}}
 \\newcounter{{col}}
 \\begin{{document}}"
-        );
+        )
+        .unwrap();
     }
-    println!("\\begin{{tikzpicture}}[scale=0.5]");
+    writeln!(output(), "\\begin{{tikzpicture}}[scale=0.5]").unwrap();
 
     let mut first_n = None;
     let mut x = 0;
     let mut y = 0;
-    while let Some(sq) = read_partial_sq_from_stdin() {
+    while let Some(mut sq) = read_partial_sq_from_stdin() {
+        if transpose {
+            sq = sq.transpose();
+        }
         let n = sq.n();
 
         if first_n.is_none() {
@@ -1112,13 +2330,15 @@ fn to_tex(standalone: bool) {
             return;
         }
 
-        println!("% {}", sq);
-        println!(
+        writeln!(output(), "% {}", sq).unwrap();
+        writeln!(
+            output(),
             "    \\begin{{scope}}[xshift = {}cm, yshift = {}cm]
         \\draw (0, 0) grid ({n}, {n});",
             x * (n + 1),
             y * (n + 1)
-        );
+        )
+        .unwrap();
 
         if x == y {
             y = x + 1;
@@ -1137,7 +2357,8 @@ fn to_tex(standalone: bool) {
                 .map(|i| format!("#{i}"))
                 .reduce(|a, b| format!("{a}, {b}"))
                 .unwrap();
-            println!(
+            writeln!(
+                output(),
                 "        \\newcommand{{\\makerow}}[{n}]{{
         \\setcounter{{col}}{{0}}
         \\foreach \\n in {{{args}}} {{
@@ -1150,45 +2371,321 @@ fn to_tex(standalone: bool) {
         }}
         \\setcounter{{row}}{{0}}",
                 n - 1
-            );
+            )
+            .unwrap();
             for i in 0..n {
-                print!("        \\makerow");
+                write!(output(), "        \\makerow").unwrap();
                 for j in 0..n {
                     if let Some(v) = sq.get_partial(i, j) {
-                        print!("{{{}}}", v + 1);
+                        write!(output(), "{{{}}}", v + 1).unwrap();
                     } else {
-                        print!("{{}}");
+                        write!(output(), "{{}}").unwrap();
                     }
                 }
-                println!();
+                writeln!(output()).unwrap();
             }
         } else {
             for i in 0..n {
                 for j in 0..n {
                     if let Some(v) = sq.get_partial(i, j) {
-                        print!(
+                        write!(
+                            output(),
                             "\\node[anchor=center] at ({j}.5, {}.5) {{{}}};",
                             n - i - 1,
                             v + 1
-                        );
+                        )
+                        .unwrap();
                     }
                 }
-                println!();
+                writeln!(output()).unwrap();
             }
         }
-        println!("    \\end{{scope}}");
+        writeln!(output(), "    \\end{{scope}}").unwrap();
     }
-    println!("\\end{{tikzpicture}}");
+    writeln!(output(), "\\end{{tikzpicture}}").unwrap();
 
     if standalone {
-        println!("\\end{{document}}");
+        writeln!(output(), "\\end{{document}}").unwrap();
+    }
+}
+
+fn to_csv(one_indexed: bool) {
+    let offset = if one_indexed { 1 } else { 0 };
+
+    while let Some(sq) = read_partial_sq_from_stdin() {
+        let n = sq.n();
+
+        for i in 0..n {
+            let row: Vec<_> = (0..n)
+                .map(|j| {
+                    sq.get_partial(i, j)
+                        .map_or(String::new(), |v| (v + offset).to_string())
+                })
+                .collect();
+            writeln!(output(), "{}", row.join(",")).unwrap();
+        }
+        writeln!(output()).unwrap()
+    }
+}
+
+fn validate() {
+    for line in input().lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let result = if isqrt(line.len()).is_none() {
+            latin_square_dyn::Error::InvalidLength { len: line.len() }.to_string()
+        } else if let Some((index, char)) = line
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_hexdigit())
+        {
+            latin_square_dyn::Error::InvalidChar { index, char }.to_string()
+        } else {
+            let values: Vec<_> = line
+                .chars()
+                .map(|c| c.to_digit(16).unwrap() as u8)
+                .collect();
+
+            match LatinSquareDyn::validation_report(&values) {
+                Ok(()) => "ok".to_string(),
+                Err(conflicts) => conflicts
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            }
+        };
+
+        if writeln!(output(), "{result}").is_err() {
+            return;
+        }
+    }
+}
+
+fn from_json() {
+    let _ = write_from_json(&mut input(), &mut output());
+}
+
+/// Converts the JSON arrays read from `reader` line by line, arrays may span several lines
+fn write_from_json(reader: &mut impl BufRead, writer: &mut impl Write) -> std::io::Result<()> {
+    let mut depth = 0usize;
+    let mut array = String::new();
+
+    let mut bytes = Vec::new();
+    for line_number in 1.. {
+        bytes.clear();
+        if reader.read_until(b'\n', &mut bytes)? == 0 {
+            break;
+        }
+
+        let Ok(line) = std::str::from_utf8(&bytes) else {
+            eprintln!("Invalid UTF-8 in line {line_number}");
+            depth = 0;
+            array.clear();
+            continue;
+        };
+
+        // split the input into the top level arrays
+        for c in line.chars() {
+            match c {
+                '[' => {
+                    depth += 1;
+                }
+                ']' if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        array.push(c);
+                        match LatinSquareDyn::from_json(&array) {
+                            Ok(sq) => writeln!(writer, "{sq}")?,
+                            Err(err) => eprintln!("{err}"),
+                        }
+                        array.clear();
+                    }
+                }
+                _ => {}
+            }
+
+            if depth > 0 {
+                array.push(c);
+            }
+        }
+    }
+
+    if depth != 0 {
+        eprintln!("Unexpected end of input");
+    }
+
+    Ok(())
+}
+
+fn to_json() {
+    while let Some(sq) = read_sq_from_stdin() {
+        if writeln!(output(), "{}", sq.to_json()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Parses a conjugate written like `permutation.apply_array(['R', 'C', 'S'])`
+fn parse_rcs(value: &str) -> Result<Permutation<3>, String> {
+    Permutation::from_rcs_name(value).ok_or_else(|| {
+        latin_square::Error::InvalidConjugate {
+            name: value.to_string(),
+        }
+        .to_string()
+    })
+}
+
+fn conjugate(rcs: &Permutation<3>) {
+    while let Some(sq) = read_sq_from_stdin() {
+        if writeln!(output(), "{}", sq.permuted_rcs(rcs)).is_err() {
+            return;
+        }
+    }
+}
+
+fn reduce<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        if writeln!(output(), "{}", sq.reduced()).is_err() {
+            return;
+        }
+    }
+}
+
+fn neighbors<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let mut stdout = output();
+        if writeln!(stdout, "{sq}").is_err() {
+            return;
+        }
+        for neighbor in sq.intercalate_switches() {
+            if writeln!(stdout, "{neighbor}").is_err() {
+                return;
+            }
+        }
+        if writeln!(stdout).is_err() {
+            return;
+        }
+    }
+}
+
+fn random_partial(seed: u64) {
+    while let Some(sq) = read_sq_from_stdin() {
+        let partial_sq = sq.random_uniquely_completable_partial(seed);
+
+        if writeln!(output(), "{partial_sq}").is_err() {
+            return;
+        }
+    }
+}
+
+fn filter_reduced() {
+    while let Some(sq) = read_sq_from_stdin() {
+        if sq.is_reduced() && writeln!(output(), "{sq}").is_err() {
+            return;
+        }
+    }
+}
+
+fn filter_intercalate_free<const N: usize>() {
+    let sqs = std::iter::from_fn(read_sq_from_stdin_n::<N>);
+
+    for sq in intercalate_free(sqs) {
+        if writeln!(output(), "{sq}").is_err() {
+            return;
+        }
+    }
+}
+
+fn transpose() {
+    let _ = write_transposed(&mut input(), &mut output());
+}
+
+fn write_transposed(reader: &mut impl BufRead, writer: &mut impl Write) -> std::io::Result<()> {
+    while let Some(sq) = read_partial_sq_from(reader) {
+        writeln!(writer, "{}", sq.transpose())?;
+    }
+
+    Ok(())
+}
+
+fn conjugates<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        let conjugates = distinct_conjugates(&sq);
+
+        let mut stdout = output();
+        if writeln!(stdout, "{sq}: {} distinct conjugates", conjugates.len()).is_err() {
+            return;
+        }
+        for (rcs, conjugate) in conjugates {
+            if writeln!(stdout, "{rcs} {conjugate}").is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Returns the distinct conjugates of `sq`, each labeled by the first RCS permutation producing it
+fn distinct_conjugates<const N: usize>(sq: &LatinSquare<N>) -> Vec<(String, LatinSquare<N>)> {
+    let mut conjugates: Vec<(String, LatinSquare<N>)> = Vec::new();
+
+    for (rcs, conjugate) in PermutationIter::<3>::new().zip(sq.conjugates()) {
+        if conjugates.iter().any(|(_, other)| *other == conjugate) {
+            continue;
+        }
+
+        let rcs = rcs.apply_array(['R', 'C', 'S']).into_iter().collect();
+        conjugates.push((rcs, conjugate));
+    }
+
+    conjugates
+}
+
+fn complete_mappings<const N: usize>() {
+    for perm in PermutationIter::<N>::new() {
+        let Some(sq) = LatinSquare::from_complete_mapping(&perm) else {
+            continue;
+        };
+
+        let perm = perm.as_array().map(|i| i.to_string()).join(",");
+        if writeln!(output(), "{perm} {sq}").is_err() {
+            return;
+        }
+    }
+}
+
+fn generate_diagonal(n: usize) {
+    for sq in DiagonalSquareGenerator::new(n) {
+        if writeln!(output(), "{sq}").is_err() {
+            return;
+        }
+    }
+}
+
+fn filter_diagonal<const N: usize>() {
+    while let Some(sq) = read_sq_from_stdin_n::<N>() {
+        if sq.is_diagonal() && writeln!(output(), "{sq}").is_err() {
+            return;
+        }
     }
 }
 
+fn intercalate_free<const N: usize>(
+    sqs: impl Iterator<Item = LatinSquare<N>>,
+) -> impl Iterator<Item = LatinSquare<N>> {
+    sqs.filter(|sq| sq.num_intercalates() == 0)
+}
+
 fn encode<const N: usize>() {
     let mut prev_sq = None;
     let mut buffer = Vec::new();
-    let mut stdout = stdout();
+    let mut stdout = output();
 
     while let Some(sq) = read_sq_from_stdin_n::<N>() {
         encode_sq::<N>(sq, prev_sq, &mut buffer);
@@ -1200,44 +2697,63 @@ fn encode<const N: usize>() {
     }
 }
 
-const fn row_size<const N: usize>() -> usize {
-    let row_size_bits = (N - 1).pow(N as u32 - 2).next_power_of_two().ilog2();
+/// The number of bytes needed to encode a row of a reduced square of order `n`
+const fn row_size(n: usize) -> usize {
+    if n <= 2 {
+        return 0;
+    }
+
+    let row_size_bits = (n - 1).pow(n as u32 - 2).next_power_of_two().ilog2();
     row_size_bits.div_ceil(8) as usize
 }
 
 fn decode<const N: usize>() {
-    let row_size_bytes = row_size::<N>();
-    let mut stdin = stdin();
+    let mut stdin = input();
 
     let mut prev_sq = None;
 
     loop {
-        let mut same_rows = [0u8];
-        match stdin.read_exact(&mut same_rows) {
-            Ok(_) => {}
-            Err(err) => match err.kind() {
-                std::io::ErrorKind::UnexpectedEof => {
+        match read_encoded_sq::<N>(&mut stdin, prev_sq.as_ref()) {
+            Ok(Some(sq)) => {
+                prev_sq = Some(sq);
+
+                if writeln!(output(), "{sq}").is_err() {
                     return;
                 }
-                _ => {
-                    panic!("{err}")
-                }
-            },
+            }
+            Ok(None) => return,
+            Err(err) => panic!("{err}"),
         }
-        let same_rows = same_rows[0];
-        assert!(same_rows <= N as u8);
-
-        let mut buffer = [[0u8; 8]; N];
+    }
+}
 
-        for i in 0..N - 1 - same_rows as usize {
-            stdin.read_exact(&mut buffer[i][0..row_size_bytes]).unwrap();
-        }
+/// Reads the next encoded square from `reader`.
+/// Returns `Ok(None)` if the input ends before a new record starts,
+/// an input that ends in the middle of a record is an error
+fn read_encoded_sq<const N: usize>(
+    reader: &mut impl Read,
+    prev_sq: Option<&LatinSquare<N>>,
+) -> std::io::Result<Option<LatinSquare<N>>> {
+    let row_size_bytes = row_size(N);
+
+    let mut same_rows = [0u8];
+    match reader.read_exact(&mut same_rows) {
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let same_rows = same_rows[0] as usize;
+    assert!(same_rows <= N);
 
-        let sq = decode_sq(prev_sq.as_ref(), same_rows.into(), &buffer);
-        prev_sq = Some(sq);
+    let mut buffer = [[0u8; 8]; N];
 
-        println!("{}", sq);
+    for i in 0..(N - 1).saturating_sub(same_rows) {
+        reader
+            .read_exact(&mut buffer[i][0..row_size_bytes])
+            .map_err(|err| std::io::Error::new(err.kind(), format!("Truncated record: {err}")))?;
     }
+
+    Ok(Some(decode_sq(prev_sq, same_rows, &buffer)))
 }
 
 fn encode_sq<const N: usize>(
@@ -1248,7 +2764,7 @@ fn encode_sq<const N: usize>(
     debug_assert!(sq.is_reduced());
     debug_assert!(prev_sq.is_none_or(|s| s.is_reduced()));
 
-    let row_size_bytes = row_size::<N>();
+    let row_size_bytes = row_size(N);
 
     let same_rows = if let Some(prev_sq) = prev_sq {
         sq.num_same_rows(&prev_sq)
@@ -1331,9 +2847,176 @@ fn decode_sq<const N: usize>(
     LatinSquare::try_from(rows).unwrap()
 }
 
+fn encode_dyn() {
+    let mut prev_sq = None;
+    let mut buffer = Vec::new();
+    let mut stdout = output();
+
+    while let Some(sq) = read_sq_from_stdin() {
+        if !sq.is_reduced() {
+            eprintln!("Square is not reduced: {sq}");
+            continue;
+        }
+
+        encode_sq_dyn(&sq, prev_sq.as_ref(), &mut buffer);
+
+        if stdout.write_all(&buffer).is_err() {
+            return;
+        }
+
+        prev_sq = Some(sq);
+        buffer.clear();
+    }
+}
+
+fn decode_dyn() {
+    let mut stdin = input();
+
+    let mut prev_sq = None;
+
+    loop {
+        match read_encoded_sq_dyn(&mut stdin, prev_sq.as_ref()) {
+            Ok(Some(sq)) => {
+                if writeln!(output(), "{sq}").is_err() {
+                    return;
+                }
+
+                prev_sq = Some(sq);
+            }
+            Ok(None) => return,
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
+
+/// Same as `encode_sq`, but each record starts with the order of the square.
+/// Rows are only shared with the previous square if both have the same order
+fn encode_sq_dyn(sq: &LatinSquareDyn, prev_sq: Option<&LatinSquareDyn>, buffer: &mut Vec<u8>) {
+    debug_assert!(sq.is_reduced());
+
+    let n = sq.n();
+    let row_size_bytes = row_size(n);
+
+    let same_rows = prev_sq.map_or(0, |prev_sq| sq.num_same_rows(prev_sq));
+
+    buffer.push(n as u8);
+    buffer.push(same_rows as u8);
+
+    for row_index in same_rows..n.saturating_sub(1) {
+        let row = sq.get_row(row_index);
+
+        let mut coded = 0u64;
+        for i in 1..n - 1 {
+            coded *= n as u64 - 1;
+
+            let value = if row[i] > row[0] { row[i] - 1 } else { row[i] };
+
+            coded += value as u64;
+        }
+
+        buffer.extend(&coded.to_le_bytes()[0..row_size_bytes]);
+    }
+}
+
+/// Reads the next square written by `encode_sq_dyn` from `reader`.
+/// Returns `Ok(None)` if the input ends before a new record starts
+fn read_encoded_sq_dyn(
+    reader: &mut impl Read,
+    prev_sq: Option<&LatinSquareDyn>,
+) -> std::io::Result<Option<LatinSquareDyn>> {
+    let mut header = [0u8; 2];
+    match reader.read_exact(&mut header[0..1]) {
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    reader
+        .read_exact(&mut header[1..2])
+        .map_err(|err| std::io::Error::new(err.kind(), format!("Truncated record: {err}")))?;
+
+    let n = header[0] as usize;
+    let same_rows = header[1] as usize;
+
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+
+    if n == 0 || n > 16 {
+        return Err(invalid(format!("Invalid order: {n}")));
+    }
+    if same_rows > n || (same_rows != 0 && prev_sq.is_none_or(|prev_sq| prev_sq.n() != n)) {
+        return Err(invalid(format!(
+            "Invalid number of shared rows: {same_rows}"
+        )));
+    }
+
+    let row_size_bytes = row_size(n);
+
+    let mut values = vec![0u8; n * n];
+
+    if let Some(prev_sq) = prev_sq {
+        values[0..same_rows * n].copy_from_slice(&prev_sq.values()[0..same_rows * n]);
+    }
+
+    let mut cols = vec![BitSet16::all_less_than(n); n];
+    for i in 0..same_rows {
+        for j in 0..n {
+            cols[j].remove(values[i * n + j].into());
+        }
+    }
+
+    for i in same_rows..n.saturating_sub(1) {
+        let mut buffer = [0u8; 8];
+        reader
+            .read_exact(&mut buffer[0..row_size_bytes])
+            .map_err(|err| std::io::Error::new(err.kind(), format!("Truncated record: {err}")))?;
+        let mut coded = u64::from_le_bytes(buffer);
+
+        let row = &mut values[i * n..(i + 1) * n];
+        row[0] = i as u8;
+        cols[0].remove(i);
+
+        let mut left = BitSet16::all_less_than(n);
+        left.remove(i);
+
+        for j in (1..n - 1).rev() {
+            let value = (coded % (n - 1) as u64) as u8;
+            coded /= (n - 1) as u64;
+
+            let value = if value >= i as u8 { value + 1 } else { value };
+
+            row[j] = value;
+            left.remove(value.into());
+            cols[j].remove(value.into());
+        }
+
+        if !left.is_single() {
+            return Err(invalid(format!("Invalid row {i}")));
+        }
+        let value = left.into_iter().next().unwrap() as u8;
+        row[n - 1] = value;
+        cols[n - 1].remove(value.into());
+    }
+
+    if same_rows < n {
+        for (j, col) in cols.iter().enumerate() {
+            if !col.is_single() {
+                return Err(invalid(format!("Invalid column {j}")));
+            }
+            values[(n - 1) * n + j] = col.into_iter().next().unwrap() as u8;
+        }
+    }
+
+    LatinSquareDyn::from_boxed_slice(values.into_boxed_slice())
+        .map(Some)
+        .ok_or_else(|| invalid("Decoded square is not a latin square".into()))
+}
+
 fn read_sq_from_stdin() -> Option<LatinSquareDyn> {
+    read_sq(&mut input())
+}
+
+fn read_sq(reader: &mut impl BufRead) -> Option<LatinSquareDyn> {
     let mut line = String::new();
-    while stdin().read_line(&mut line).is_ok_and(|i| i != 0) {
+    while reader.read_line(&mut line).is_ok_and(|i| i != 0) {
         line = line.trim().into(); // remove newline
         match LatinSquareDyn::try_from(line.as_str()) {
             Ok(sq) => {
@@ -1351,8 +3034,12 @@ fn read_sq_from_stdin() -> Option<LatinSquareDyn> {
 }
 
 fn read_sq_from_stdin_n<const N: usize>() -> Option<LatinSquare<N>> {
+    read_sq_n(&mut input())
+}
+
+fn read_sq_n<const N: usize>(reader: &mut impl BufRead) -> Option<LatinSquare<N>> {
     let mut line = String::new();
-    while stdin().read_line(&mut line).is_ok_and(|i| i != 0) {
+    while reader.read_line(&mut line).is_ok_and(|i| i != 0) {
         line = line.trim().into(); // remove newline
         match LatinSquare::try_from(line.as_str()) {
             Ok(sq) => {
@@ -1369,28 +3056,28 @@ fn read_sq_from_stdin_n<const N: usize>() -> Option<LatinSquare<N>> {
     None
 }
 
+/// Reads a square either as a single line or as a grid, see `PartialLatinSquareDyn::parse_line_or_grid`
 fn read_partial_sq_from_stdin() -> Option<PartialLatinSquareDyn> {
-    let mut line = String::new();
-    while stdin().read_line(&mut line).is_ok_and(|i| i != 0) {
-        line = line.trim().into(); // remove newline
-        match PartialLatinSquareDyn::try_from(line.as_str()) {
-            Ok(sq) => {
-                line.clear();
-                return Some(sq);
-            }
-            Err(err) => {
-                eprintln!("{err}");
-                line.clear();
-                continue;
-            }
+    read_partial_sq_from(&mut input())
+}
+
+fn read_partial_sq_from(reader: &mut impl BufRead) -> Option<PartialLatinSquareDyn> {
+    let mut lines = reader.lines().map_while(Result::ok);
+    loop {
+        match PartialLatinSquareDyn::parse_line_or_grid(&mut lines)? {
+            Ok(sq) => return Some(sq),
+            Err(err) => eprintln!("{err}"),
         }
     }
-    None
 }
 
 fn read_mols_from_stdin<const N: usize>() -> Option<Mols<N>> {
+    read_mols(&mut input())
+}
+
+fn read_mols<const N: usize>(reader: &mut impl BufRead) -> Option<Mols<N>> {
     let mut line = String::new();
-    while stdin().read_line(&mut line).is_ok_and(|i| i != 0) {
+    while reader.read_line(&mut line).is_ok_and(|i| i != 0) {
         line = line.trim().into(); // remove newline
         match Mols::try_from(line.as_str()) {
             Ok(mols) => {
@@ -1406,3 +3093,530 @@ fn read_mols_from_stdin<const N: usize>() -> Option<Mols<N>> {
     }
     None
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn main_class_counts() {
+        let lookup = generate_minimize_rows_lookup();
+
+        let z4 = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+        let isotopic = z4
+            .permuted_rows(&Permutation::from_array([1, 3, 0, 2]))
+            .permuted_vals(&Permutation::from_array([2, 0, 3, 1]));
+        assert_ne!(z4, isotopic);
+
+        let counts = super::main_class_counts([z4, isotopic].into_iter(), &lookup);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts.values().copied().collect::<Vec<_>>(), vec![2]);
+
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let counts = super::main_class_counts([z4, klein, isotopic].into_iter(), &lookup);
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn write_transversal_counts() {
+        let path = std::env::temp_dir().join(format!(
+            "latin_squares_transversals_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "0123103223013210\n0123123023013012\n").unwrap();
+
+        let mut reader = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let mut output = Vec::new();
+        super::write_transversal_counts::<4>(&mut reader, &mut output, 2).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "8\n0\n");
+    }
+
+    #[test]
+    fn format_difficulty() {
+        let sq = PartialLatinSquareDyn::try_from("012120.0.").unwrap();
+        assert_eq!(super::format_difficulty(&sq), "2 2,0:1");
+
+        let full = PartialLatinSquareDyn::try_from("012120201").unwrap();
+        assert_eq!(super::format_difficulty(&full), "0");
+
+        let empty = PartialLatinSquareDyn::empty(3);
+        assert_eq!(super::format_difficulty(&empty), "27 0,0:3");
+    }
+
+    #[test]
+    fn write_from_json() {
+        let mut input =
+            b"[[0,1],[1,0]] [[0,1,2],\n [1,2,0],\n [2,0,1]]\n[[\xff]]\n[[0]]\n".as_slice();
+        let mut output = Vec::new();
+        super::write_from_json(&mut input, &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "0110\n012120201\n0\n");
+    }
+
+    #[test]
+    fn transpose_twice() {
+        let sq = "0.2.41..0.....1.40..4..2.";
+
+        let mut transposed = Vec::new();
+        super::write_transposed(&mut format!("{sq}\n").as_bytes(), &mut transposed).unwrap();
+        assert_ne!(String::from_utf8(transposed.clone()).unwrap().trim(), sq);
+
+        let mut twice = Vec::new();
+        super::write_transposed(&mut transposed.as_slice(), &mut twice).unwrap();
+        assert_eq!(String::from_utf8(twice).unwrap(), format!("{sq}\n"));
+    }
+
+    #[test]
+    fn skip_isotopy_classes() {
+        let mut first = Vec::new();
+        super::write_isotopy_classes::<5>(&mut first, &HashSet::new(), false).unwrap();
+        assert!(!first.is_empty());
+
+        let mut reader = first.as_slice();
+        let skip: HashSet<_> = std::iter::from_fn(|| read_sq_n::<5>(&mut reader)).collect();
+        assert_eq!(skip.len(), 2);
+
+        let mut second = Vec::new();
+        super::write_isotopy_classes::<5>(&mut second, &skip, false).unwrap();
+        assert!(second.is_empty());
+    }
+
+    #[test]
+    fn count_subtransversals() {
+        // the elementary abelian group of order 9, which has subsquares of order 3
+        let sq = LatinSquare::<9>::new(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i % 3 + j % 3) % 3 + (i / 3 + j / 3) % 3 * 3) as u8)
+        }));
+
+        let details = super::subtransversals(&sq, 3);
+        let (subsquares, total) = super::count_subtransversals(&sq, 3);
+
+        assert_eq!(subsquares, details.len());
+        assert_eq!(total, details.iter().map(|(_, t)| t.len()).sum::<usize>());
+        assert!(subsquares > 0 && subsquares <= sq.subsquares_bitset(3).len());
+
+        for (subsquare, transversals) in &details {
+            for transversal in transversals {
+                assert_eq!(subsquare.intersect(*transversal).len(), 3);
+            }
+        }
+
+        // the diagonal cells of an intercalate have the same symbol
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        assert_eq!(super::count_subtransversals(&klein, 2), (0, 0));
+
+        let z4 = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+        assert_eq!(super::count_subtransversals(&z4, 2), (0, 0));
+    }
+
+    #[test]
+    fn stats_json() {
+        let sq = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let json = super::stats_json(&sq);
+
+        let inner = json.strip_prefix('{').unwrap().strip_suffix('}').unwrap();
+        let fields: HashMap<_, _> = inner
+            .split(',')
+            .map(|field| {
+                let (key, value) = field.split_once(':').unwrap();
+                (key.trim_matches('"'), value)
+            })
+            .collect();
+
+        assert_eq!(fields.len(), 6);
+        assert_eq!(fields["n"], "4");
+        assert_eq!(fields["transversals"], "8");
+        assert_eq!(fields["intercalates"], "12");
+        assert_eq!(fields["max_disjoint_transversals"], "4");
+        assert_eq!(fields["is_reduced"], "true");
+    }
+
+    #[test]
+    fn apply_permutations() {
+        let sq = PartialLatinSquareDyn::try_from("0123103223013210").unwrap();
+        let permutations = [
+            PermutationDyn::try_from("2,0,3,1").unwrap(),
+            PermutationDyn::try_from("1,3,0,2").unwrap(),
+            PermutationDyn::try_from("3,2,0,1").unwrap(),
+        ];
+
+        let mut permuted = sq.clone();
+        super::apply_permutations(&mut permuted, &permutations.clone().map(Some)).unwrap();
+        assert_ne!(permuted, sq);
+
+        let inverses = permutations.map(|p| Some(p.inverse()));
+        super::apply_permutations(&mut permuted, &inverses).unwrap();
+        assert_eq!(permuted, sq);
+
+        let mut only_rows = sq.clone();
+        let rows = [
+            Some(PermutationDyn::try_from("1,0,2,3").unwrap()),
+            None,
+            None,
+        ];
+        super::apply_permutations(&mut only_rows, &rows).unwrap();
+        assert_eq!(
+            only_rows,
+            PartialLatinSquareDyn::try_from("1032012323013210").unwrap()
+        );
+
+        let wrong_len = [Some(PermutationDyn::try_from("1,0,2").unwrap()), None, None];
+        assert!(super::apply_permutations(&mut only_rows, &wrong_len).is_err());
+    }
+
+    #[test]
+    fn distinct_conjugates() {
+        let klein = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let conjugates = super::distinct_conjugates(&klein);
+        assert_eq!(conjugates, vec![("RCS".to_string(), klein)]);
+
+        let sq = LatinSquare::new([
+            [0, 1, 2, 3, 4],
+            [1, 0, 3, 4, 2],
+            [2, 3, 4, 0, 1],
+            [3, 4, 1, 2, 0],
+            [4, 2, 0, 1, 3],
+        ]);
+        let conjugates = super::distinct_conjugates(&sq);
+        assert!(conjugates.len() > 1);
+        assert_eq!(conjugates[0], ("RCS".to_string(), sq));
+        for (rcs, conjugate) in &conjugates {
+            assert_eq!(rcs.len(), 3);
+            assert_eq!(conjugates.iter().filter(|(_, c)| c == conjugate).count(), 1);
+        }
+    }
+
+    #[test]
+    fn format_diff() {
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+        let switched = LatinSquareDyn::try_from("1023013223013210").unwrap();
+
+        assert_eq!(
+            super::format_diff(&sq, &switched),
+            "+---+---+---+---+\n\
+             | X | X | 2 | 3 |\n\
+             +---+---+---+---+\n\
+             | X | X | 3 | 2 |\n\
+             +---+---+---+---+\n\
+             | 2 | 3 | 0 | 1 |\n\
+             +---+---+---+---+\n\
+             | 3 | 2 | 1 | 0 |\n\
+             +---+---+---+---+\n"
+        );
+        assert!(!super::format_diff(&sq, &sq).contains('X'));
+    }
+
+    #[test]
+    fn critical_set_status() {
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+
+        let critical = super::smallest_critical_sets(&sq, sq.differences(), false)
+            .into_iter()
+            .next()
+            .unwrap();
+        assert_eq!(super::critical_set_status(&sq, &critical), "critical");
+
+        let full = PartialLatinSquareDyn::from(&sq);
+        assert_eq!(
+            super::critical_set_status(&sq, &full),
+            "uniquely completable but not minimal"
+        );
+
+        let empty = PartialLatinSquareDyn::empty(4);
+        assert_eq!(
+            super::critical_set_status(&sq, &empty),
+            "not uniquely completable"
+        );
+    }
+
+    #[test]
+    fn smallest_critical_sets() {
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+
+        let first = super::smallest_critical_sets(&sq, sq.differences(), false);
+        let all = super::smallest_critical_sets(&sq, sq.differences(), true);
+
+        assert_eq!(first.len(), 1);
+        assert!(all.len() >= 2);
+        assert!(all.contains(&first[0]));
+
+        for (i, partial_sq) in all.iter().enumerate() {
+            assert_eq!(partial_sq.num_entries(), first[0].num_entries());
+            assert!(partial_sq.is_uniquely_completable_to(&sq));
+            assert!(!all[i + 1..].contains(partial_sq));
+        }
+    }
+
+    #[test]
+    fn subsquare_partials() {
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+
+        for k in 1..=4 {
+            let partials = super::subsquare_partials(&sq, k);
+            assert_eq!(partials.len(), sq.num_subsquares_dyn(k));
+
+            for partial_sq in partials {
+                let n = partial_sq.n();
+                let rows: Vec<_> = (0..n)
+                    .filter(|i| (0..n).any(|j| partial_sq.get_partial(*i, j).is_some()))
+                    .collect();
+                let cols: Vec<_> = (0..n)
+                    .filter(|j| (0..n).any(|i| partial_sq.get_partial(i, *j).is_some()))
+                    .collect();
+                assert_eq!(rows.len(), k);
+                assert_eq!(cols.len(), k);
+                assert_eq!(partial_sq.num_entries(), k * k);
+                assert!(partial_sq.is_valid());
+
+                let symbols: BitSet16 = rows
+                    .iter()
+                    .flat_map(|i| cols.iter().map(|j| partial_sq.get_partial(*i, *j).unwrap()))
+                    .collect();
+                assert_eq!(symbols.len(), k);
+            }
+        }
+    }
+
+    #[test]
+    fn orthogonality_adjacency() {
+        let a = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let b = LatinSquare::new([[0, 1, 2, 3], [2, 3, 0, 1], [3, 2, 1, 0], [1, 0, 3, 2]]);
+        // the cyclic square has no orthogonal mate
+        let c = LatinSquare::new([[0, 1, 2, 3], [1, 2, 3, 0], [2, 3, 0, 1], [3, 0, 1, 2]]);
+
+        assert_eq!(
+            super::orthogonality_adjacency(&[a, c, b]),
+            vec![vec![2], vec![], vec![0]]
+        );
+    }
+
+    #[test]
+    fn largest_critical_sets() {
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+        let smallest = super::smallest_critical_sets(&sq, sq.differences(), false)[0].num_entries();
+
+        let capped = super::largest_critical_sets(&sq, smallest);
+        assert!(!capped.is_empty());
+        for partial_sq in &capped {
+            assert_eq!(partial_sq.num_entries(), smallest);
+            assert!(partial_sq.is_critical_set_of(&sq));
+        }
+
+        let uncapped = super::largest_critical_sets(&sq, 16);
+        assert!(uncapped[0].num_entries() > smallest);
+        for partial_sq in &uncapped {
+            assert!(partial_sq.is_critical_set_of(&sq));
+        }
+    }
+
+    #[test]
+    fn expand_main_classes() {
+        let lookup = generate_minimize_rows_lookup();
+
+        let z5 = LatinSquare::<5>::new(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i + j) % 5) as u8)
+        }));
+        let sqs = [z5, z5.transpose(), z5];
+
+        let mut visited = Vec::new();
+        let uncached =
+            super::expand_main_classes(sqs.into_iter(), &lookup, false, |sq| visited.push(*sq));
+        let mut visited_cached = Vec::new();
+        let cached = super::expand_main_classes(sqs.into_iter(), &lookup, true, |sq| {
+            visited_cached.push(*sq)
+        });
+
+        assert_eq!(uncached, cached);
+        assert_eq!(visited, visited_cached);
+        assert!(uncached.contains(&z5.main_class_lookup(&lookup)));
+    }
+
+    #[test]
+    fn intercalate_free() {
+        let z5 = LatinSquare::<5>::new(std::array::from_fn(|i| {
+            std::array::from_fn(|j| ((i + j) % 5) as u8)
+        }));
+        let with_intercalate = LatinSquare::new([
+            [0, 1, 2, 3, 4],
+            [1, 0, 3, 4, 2],
+            [2, 3, 4, 0, 1],
+            [3, 4, 1, 2, 0],
+            [4, 2, 0, 1, 3],
+        ]);
+        assert!(with_intercalate.num_intercalates() > 0);
+
+        let filtered: Vec<_> =
+            super::intercalate_free([z5, with_intercalate].into_iter()).collect();
+        assert_eq!(filtered, vec![z5]);
+    }
+
+    #[test]
+    fn encode_decode() {
+        let lookup = generate_minimize_rows_lookup_simd::<6>();
+        let sqs: Vec<_> = IsotopyClassGenerator::<6>::new(&lookup).collect();
+
+        let mut bytes = Vec::new();
+        let mut buffer = Vec::new();
+        let mut prev_sq = None;
+        for sq in &sqs {
+            encode_sq(*sq, prev_sq, &mut buffer);
+            bytes.append(&mut buffer);
+            prev_sq = Some(*sq);
+        }
+
+        let mut reader = bytes.as_slice();
+        let mut decoded = Vec::new();
+        while let Some(sq) = read_encoded_sq::<6>(&mut reader, decoded.last()).unwrap() {
+            decoded.push(sq);
+        }
+        assert_eq!(decoded, sqs);
+
+        let mut truncated = &bytes[..bytes.len() - 1];
+        let mut prev_sq = None;
+        loop {
+            match read_encoded_sq::<6>(&mut truncated, prev_sq.as_ref()) {
+                Ok(Some(sq)) => prev_sq = Some(sq),
+                Ok(None) => panic!("truncated record was not detected"),
+                Err(err) => {
+                    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode_dyn() {
+        let mut sqs = Vec::new();
+        for n in 4..=8 {
+            for sq in RandomLatinSquareGeneratorDyn::new(n, n as u64).take(10) {
+                // reduce the square by permuting columns and then rows
+                let col_perm: Vec<usize> = (0..n)
+                    .map(|v| (0..n).find(|j| sq.get(0, *j) == v).unwrap())
+                    .collect();
+                let sq = LatinSquareDyn::from_boxed_slice(
+                    (0..n * n)
+                        .map(|i| sq.get(i / n, col_perm[i % n]) as u8)
+                        .collect(),
+                )
+                .unwrap();
+                let mut rows: Vec<_> = (0..n).map(|i| sq.get_row(i).to_vec()).collect();
+                rows.sort();
+
+                sqs.push(
+                    LatinSquareDyn::from_boxed_slice(rows.concat().into_boxed_slice()).unwrap(),
+                );
+            }
+            // consecutive squares with shared rows
+            sqs.push(sqs.last().unwrap().clone());
+        }
+
+        let mut bytes = Vec::new();
+        let mut buffer = Vec::new();
+        let mut prev_sq = None;
+        for sq in &sqs {
+            assert!(sq.is_reduced());
+            encode_sq_dyn(sq, prev_sq, &mut buffer);
+            bytes.append(&mut buffer);
+            prev_sq = Some(sq);
+        }
+
+        let mut reader = bytes.as_slice();
+        let mut decoded = Vec::new();
+        while let Some(sq) = read_encoded_sq_dyn(&mut reader, decoded.last()).unwrap() {
+            decoded.push(sq);
+        }
+        assert_eq!(decoded, sqs);
+
+        let mut truncated = &bytes[..bytes.len() - 1];
+        let mut prev_sq = None;
+        loop {
+            match read_encoded_sq_dyn(&mut truncated, prev_sq.as_ref()) {
+                Ok(Some(sq)) => prev_sq = Some(sq),
+                Ok(None) => panic!("truncated record was not detected"),
+                Err(err) => {
+                    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn main_class_permutation() {
+        let lookup = generate_minimize_rows_lookup();
+
+        for sq in RandomLatinSquareGeneratorDyn::new(5, 5).take(10) {
+            let sq: LatinSquare<5> = (&sq).try_into().unwrap();
+            let (main_class, rcs, [rows, cols, vals]) = sq.main_class_permutation();
+
+            assert_eq!(main_class, sq.main_class_lookup(&lookup));
+            assert_eq!(
+                sq.permuted_rcs(&rcs)
+                    .permuted_rows(&rows)
+                    .permuted_cols(&cols)
+                    .permuted_vals(&vals),
+                main_class
+            );
+
+            let line = format_main_class_permutation(&main_class, &rcs, &[rows, cols, vals]);
+            assert_eq!(line.split(' ').count(), 5);
+            assert!(line.starts_with(&main_class.to_string()));
+        }
+    }
+
+    #[test]
+    fn critical_sets_text() {
+        let sq = LatinSquareDyn::try_from("0123103223013210").unwrap();
+
+        let mut output = Vec::new();
+        write_critical_sets_text(&sq, critical_sets(&sq), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("0123103223013210"));
+
+        let partial_sqs: Vec<_> = lines
+            .take_while(|line| !line.is_empty())
+            .map(|line| PartialLatinSquareDyn::try_from(line).unwrap())
+            .collect();
+        assert!(!partial_sqs.is_empty());
+        assert!(partial_sqs
+            .iter()
+            .all(|partial_sq| partial_sq.is_critical_set_of(&sq)));
+    }
+
+    #[test]
+    fn parse_rcs() {
+        for rcs in PermutationIter::<3>::new() {
+            let name: String = rcs.apply_array(['R', 'C', 'S']).into_iter().collect();
+            assert_eq!(super::parse_rcs(&name), Ok(rcs));
+        }
+
+        assert_eq!(
+            super::parse_rcs("CRS"),
+            Ok(Permutation::from_array([1, 0, 2]))
+        );
+        assert!(super::parse_rcs("RRS").is_err());
+        assert!(super::parse_rcs("RC").is_err());
+        assert!(super::parse_rcs("RCSX").is_err());
+    }
+
+    #[test]
+    fn transversal_counts() {
+        let sqs = [
+            LatinSquare::<2>::new([[0, 1], [1, 0]]),
+            LatinSquare::<2>::new([[1, 0], [0, 1]]),
+        ];
+        assert_eq!(super::transversal_counts(sqs.into_iter()), vec![2]);
+
+        let sqs = [
+            LatinSquare::<3>::new([[0, 1, 2], [1, 2, 0], [2, 0, 1]]),
+            LatinSquare::<3>::new([[0, 1, 2], [2, 0, 1], [1, 2, 0]]),
+        ];
+        assert_eq!(super::transversal_counts(sqs.into_iter()), vec![0, 0, 0, 2]);
+    }
+}
@@ -6,6 +6,7 @@ use std::{
 use crate::{
     bitset::{BitSet128, BitSet128Iter},
     bitvec::BitVec,
+    progress::progress,
 };
 
 type BitSet = BitSet128;
@@ -183,7 +184,12 @@ impl Iterator for MMCSHittingSetGenerator {
                         let time_passed = (Instant::now() - self.last_progress).as_secs_f64();
                         if time_passed >= 10.0 {
                             self.last_progress = Instant::now();
-                            dbg!(self.progress(), self.estimated_time_left());
+                            progress(format_args!(
+                                "hitting sets of size {}: {:.2}% done, {:?} left",
+                                self.max_entries,
+                                self.progress() * 100.0,
+                                self.estimated_time_left()
+                            ));
                         }
                         return Some(hitting_set);
                     }
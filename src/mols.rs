@@ -2,6 +2,7 @@ use std::{cmp::Ordering, fmt::Display};
 
 use crate::{
     latin_square::{self, LatinSquare},
+    orthogonal_array::OrthogonalArray,
     permutation::{Permutation, PermutationIter},
     tuple_iterator::TupleIterator,
 };
@@ -13,21 +14,71 @@ pub struct Mols<const N: usize> {
 
 impl<const N: usize> Mols<N> {
     pub fn new(sqs: Vec<LatinSquare<N>>) -> Result<Self, (usize, usize)> {
+        if let Some(indices) = Self::non_orthogonal_pair(&sqs) {
+            return Err(indices);
+        }
+
+        Ok(Mols { sqs: sqs.to_vec() })
+    }
+
+    fn non_orthogonal_pair(sqs: &[LatinSquare<N>]) -> Option<(usize, usize)> {
         for i in 0..sqs.len() {
             for j in (i + 1)..sqs.len() {
                 if !sqs[i].is_orthogonal_to(&sqs[j]) {
-                    return Err((i, j));
+                    return Some((i, j));
                 }
             }
         }
 
-        Ok(Mols { sqs: sqs.to_vec() })
+        None
+    }
+
+    /// Checks that the squares are pairwise orthogonal
+    pub fn is_valid(&self) -> bool {
+        Self::non_orthogonal_pair(&self.sqs).is_none()
+    }
+
+    /// Checks whether the squares form a complete set of N-1 MOLS
+    pub fn is_complete(&self) -> bool {
+        N >= 2 && self.len() == N - 1 && self.is_valid()
     }
 
     pub fn new_unchecked(sqs: Vec<LatinSquare<N>>) -> Self {
         Mols { sqs }
     }
 
+    /// Returns the number of squares
+    pub fn len(&self) -> usize {
+        self.sqs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sqs.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&LatinSquare<N>> {
+        self.sqs.get(i)
+    }
+
+    pub fn squares(&self) -> &[LatinSquare<N>] {
+        &self.sqs
+    }
+
+    /// Converts the squares to an orthogonal array with the rows `row col v1 v2 ...`
+    pub fn to_orthogonal_array(&self) -> OrthogonalArray<N> {
+        let mut rows = Vec::with_capacity(N * N);
+
+        for i in 0..N {
+            for j in 0..N {
+                let mut row = vec![i as u8, j as u8];
+                row.extend(self.sqs.iter().map(|sq| sq.get(i, j) as u8));
+                rows.push(row);
+            }
+        }
+
+        OrthogonalArray::new_unchecked(rows)
+    }
+
     const ROWS: [[u8; N]; N] = {
         let mut rows = [[0; N]; N];
         let mut i = 0;
@@ -368,3 +419,47 @@ impl<const N: usize> TryFrom<&str> for Mols<N> {
         Ok(mols)
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn is_complete() {
+        let a = LatinSquare::new([[0, 1, 2], [1, 2, 0], [2, 0, 1]]);
+        let b = LatinSquare::new([[0, 1, 2], [2, 0, 1], [1, 2, 0]]);
+
+        let mols = Mols::new(vec![a, b]).unwrap();
+        assert!(mols.is_valid());
+        assert!(mols.is_complete());
+
+        let not_orthogonal = Mols::new_unchecked(vec![a, a]);
+        assert!(!not_orthogonal.is_valid());
+        assert!(!not_orthogonal.is_complete());
+
+        let a = LatinSquare::new([[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]]);
+        let b = LatinSquare::new([[0, 1, 2, 3], [2, 3, 0, 1], [3, 2, 1, 0], [1, 0, 3, 2]]);
+
+        let mols = Mols::new(vec![a, b]).unwrap();
+        assert!(mols.is_valid());
+        assert!(!mols.is_complete());
+    }
+
+    #[test]
+    fn accessors() {
+        let a = LatinSquare::new([[0, 1, 2], [1, 2, 0], [2, 0, 1]]);
+        let b = LatinSquare::new([[0, 1, 2], [2, 0, 1], [1, 2, 0]]);
+
+        let mols = Mols::new(vec![a, b]).unwrap();
+
+        assert_eq!(mols.len(), 2);
+        assert!(!mols.is_empty());
+        assert_eq!(mols.get(0), Some(&a));
+        assert_eq!(mols.get(1), Some(&b));
+        assert_eq!(mols.get(2), None);
+        assert_eq!(mols.squares(), &[a, b]);
+
+        assert_eq!(Mols::new(vec![a, a]), Err((0, 1)));
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+use crate::{
+    bitset::{BitSet128, BitSet256},
+    tuple_iterator::TupleIterator,
+};
+
+/// An orthogonal array OA(k, N) of strength 2 with N² rows and k columns
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrthogonalArray<const N: usize> {
+    rows: Vec<Vec<u8>>,
+}
+
+impl<const N: usize> OrthogonalArray<N> {
+    pub fn new_unchecked(rows: Vec<Vec<u8>>) -> Self {
+        debug_assert!(Self::is_valid(&rows));
+
+        OrthogonalArray { rows }
+    }
+
+    pub fn rows(&self) -> &[Vec<u8>] {
+        &self.rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    /// Checks that every pair of columns contains every ordered pair of symbols exactly once
+    pub fn is_valid(rows: &[Vec<u8>]) -> bool {
+        if rows.len() != N * N {
+            return false;
+        }
+
+        let k = rows[0].len();
+        if rows
+            .iter()
+            .any(|row| row.len() != k || row.iter().any(|val| *val as usize >= N))
+        {
+            return false;
+        }
+
+        TupleIterator::<2>::new(k).all(|[i, j]| {
+            let pairs: BitSet256 = rows
+                .iter()
+                .map(|row| row[i] as usize * N + row[j] as usize)
+                .collect();
+
+            pairs.len() == N * N
+        })
+    }
+
+    /// Returns the unavoidable sets of order 1, i.e. the sets of rows that change
+    /// when swapping two symbols in a single column.
+    /// The rows are identified with the cells `(row[0], row[1])`, so each set is a mask of cells
+    pub fn unavoidable_sets_order_1(&self) -> Vec<BitSet128> {
+        assert!(N * N <= 128);
+        assert!(self.num_columns() >= 2);
+
+        let cell = |row: &[u8]| row[0] as usize * N + row[1] as usize;
+
+        let mut rows_by_cell = vec![&self.rows[0]; N * N];
+        for row in &self.rows {
+            rows_by_cell[cell(row)] = row;
+        }
+
+        let mut sets = Vec::new();
+        for column in 0..self.num_columns() {
+            for [a, b] in TupleIterator::<2>::new(N) {
+                let (a, b) = (a as u8, b as u8);
+
+                let mut set = BitSet128::empty();
+                for row in &self.rows {
+                    let mut row = row.clone();
+                    if row[column] == a {
+                        row[column] = b;
+                    } else if row[column] == b {
+                        row[column] = a;
+                    }
+
+                    let cell = cell(&row);
+                    if rows_by_cell[cell] != &row {
+                        set.insert(cell);
+                    }
+                }
+
+                sets.push(set);
+            }
+        }
+
+        sets.sort();
+        sets.dedup();
+        sets
+    }
+}
+
+impl<const N: usize> Display for OrthogonalArray<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.rows {
+            let row: Vec<_> = row.iter().map(|val| val.to_string()).collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        latin_square::LatinSquare, latin_square_generator::LatinSquareGeneratorDyn, mols::Mols,
+    };
+
+    use super::*;
+
+    #[test]
+    fn from_mols() {
+        let sqs = [
+            [[0, 1, 2, 3], [1, 0, 3, 2], [2, 3, 0, 1], [3, 2, 1, 0]],
+            [[0, 1, 2, 3], [2, 3, 0, 1], [3, 2, 1, 0], [1, 0, 3, 2]],
+            [[0, 1, 2, 3], [3, 2, 1, 0], [1, 0, 3, 2], [2, 3, 0, 1]],
+        ];
+        let mols = Mols::new(sqs.map(LatinSquare::new).to_vec()).unwrap();
+
+        let oa = mols.to_orthogonal_array();
+        assert_eq!(oa.rows().len(), 16);
+        assert_eq!(oa.num_columns(), 5);
+        assert!(OrthogonalArray::<4>::is_valid(oa.rows()));
+        assert_eq!(oa.rows()[6], vec![1, 2, 3, 0, 1]);
+
+        let mut rows = oa.rows().to_vec();
+        rows[0][2] = 1;
+        assert!(!OrthogonalArray::<4>::is_valid(&rows));
+    }
+
+    #[test]
+    fn unavoidable_sets_order_1() {
+        let a = LatinSquare::new([[0, 1, 2], [1, 2, 0], [2, 0, 1]]);
+        let b = LatinSquare::new([[0, 1, 2], [2, 0, 1], [1, 2, 0]]);
+        let oa = Mols::new(vec![a, b]).unwrap().to_orthogonal_array();
+
+        let sqs: Vec<LatinSquare<3>> = LatinSquareGeneratorDyn::new(3)
+            .map(|sq| LatinSquare::try_from(&sq).unwrap())
+            .collect();
+        let others: Vec<_> = sqs
+            .iter()
+            .flat_map(|a| sqs.iter().map(|b| vec![*a, *b]))
+            .filter_map(|sqs| Mols::new(sqs).ok())
+            .map(|mols| mols.to_orthogonal_array())
+            .filter(|other| other != &oa)
+            .collect();
+
+        let sets = oa.unavoidable_sets_order_1();
+        assert!(!sets.is_empty());
+
+        for set in sets {
+            assert!(!set.is_empty());
+
+            // some other completion agrees with `oa` outside of `set`
+            assert!(others.iter().any(|other| {
+                (0..9)
+                    .filter(|cell| !set.contains(*cell))
+                    .all(|cell| other.rows()[cell] == oa.rows()[cell])
+            }));
+        }
+    }
+}
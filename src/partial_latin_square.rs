@@ -4,7 +4,10 @@ use std::{
     fmt::{Display, Write},
 };
 
-use crate::{bitset::BitSet16, latin_square::LatinSquare};
+use crate::{
+    bitset::{BitSet128, BitSet16},
+    latin_square::LatinSquare,
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PartialLatinSquare<const N: usize> {
@@ -66,6 +69,20 @@ impl<const N: usize> PartialLatinSquare<N> {
             .sum()
     }
 
+    /// Removes all entries
+    pub fn clear(&mut self) {
+        *self = Self::empty();
+    }
+
+    /// Returns the cells with an entry, where cell `(i, j)` has index `i * N + j`
+    pub fn filled_cells_bitset(&self) -> BitSet128 {
+        assert!(N * N <= 128);
+
+        (0..N * N)
+            .filter(|index| self.get_partial(index / N, index % N).is_some())
+            .collect()
+    }
+
     pub fn cmp_rows(&self, other: &Self) -> Ordering {
         for i in 0..N {
             for j in 0..N {
@@ -196,4 +213,27 @@ impl<const N: usize> Debug for PartialLatinSquare<N> {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn num_entries() {
+        let mut sq = PartialLatinSquare::<4>::try_from("01..10....01..10").unwrap();
+
+        assert_eq!(sq.num_entries(), 8);
+        assert_eq!(
+            sq.filled_cells_bitset(),
+            BitSet128::from_slice(&[0, 1, 4, 5, 10, 11, 14, 15])
+        );
+
+        sq.set(0, 0, None);
+        assert_eq!(sq.num_entries(), 7);
+        assert!(!sq.filled_cells_bitset().contains(0));
+
+        sq.clear();
+        assert_eq!(sq.num_entries(), 0);
+        assert_eq!(sq.filled_cells_bitset(), BitSet128::empty());
+        assert_eq!(sq, PartialLatinSquare::empty());
+    }
+}
@@ -1,8 +1,10 @@
 use std::fmt::{Debug, Display, Write};
 
 use crate::{
+    bitset::{BitSet128, BitSet16},
     latin_square_dyn::{isqrt, LatinSquareDyn},
     latin_square_generator::LatinSquareGeneratorDyn,
+    permutation::Permutation,
     permutation_dyn::PermutationDyn,
 };
 
@@ -20,10 +22,95 @@ impl PartialLatinSquareDyn {
         }
     }
 
+    /// Parses a square from one line per row of whitespace or comma separated values.
+    /// Empty cells are given as `.`, `-` or an empty field
+    pub fn from_grid(lines: &[&str]) -> Result<Self, Error> {
+        let n = lines.len();
+        let mut sq = Self::empty(n);
+
+        for (i, line) in lines.iter().enumerate() {
+            let fields = split_grid_row(line);
+
+            if fields.len() != n {
+                return Err(Error::InvalidRowLength {
+                    row: i,
+                    len: fields.len(),
+                    expected: n,
+                });
+            }
+
+            for (j, field) in fields.into_iter().enumerate() {
+                if matches!(field, "" | "." | "-") {
+                    continue;
+                }
+
+                let entry = field
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|entry| *entry < n)
+                    .ok_or_else(|| Error::InvalidEntry {
+                        row: i,
+                        col: j,
+                        entry: field.to_string(),
+                    })?;
+                sq.set(i, j, Some(entry));
+            }
+        }
+
+        if !sq.is_valid() {
+            return Err(Error::NotALatinSquare);
+        }
+
+        Ok(sq)
+    }
+
+    /// Parses the next square from `lines`, skipping blank lines. A line of hex digits and dots
+    /// is a whole square, otherwise the line is the first row of a grid separated by spaces
+    /// or commas and the following lines are read until there are as many rows as entries.
+    /// Returns `None` if there are no lines left
+    pub fn parse_line_or_grid<S: AsRef<str>>(
+        lines: &mut impl Iterator<Item = S>,
+    ) -> Option<Result<Self, Error>> {
+        let first = lines.find(|line| !line.as_ref().trim().is_empty())?;
+        let first = first.as_ref().trim();
+
+        if first.chars().all(|c| c.is_ascii_hexdigit() || c == '.') {
+            return Some(Self::try_from(first));
+        }
+
+        let n = split_grid_row(first).len();
+        let mut rows = vec![first.to_string()];
+        while rows.len() < n {
+            let Some(line) = lines.next() else {
+                return Some(Err(Error::MissingRows {
+                    rows: rows.len(),
+                    expected: n,
+                }));
+            };
+            rows.push(line.as_ref().trim().to_string());
+        }
+
+        let rows: Vec<_> = rows.iter().map(|row| row.as_str()).collect();
+        Some(Self::from_grid(&rows))
+    }
+
     pub fn n(&self) -> usize {
         self.n
     }
 
+    /// Checks that no value appears twice in a row or column
+    pub fn is_valid(&self) -> bool {
+        let n = self.n;
+
+        (0..n).all(|i| {
+            let row: Vec<_> = (0..n).filter_map(|j| self.get_partial(i, j)).collect();
+            let col: Vec<_> = (0..n).filter_map(|j| self.get_partial(j, i)).collect();
+
+            row.iter().copied().collect::<BitSet16>().len() == row.len()
+                && col.iter().copied().collect::<BitSet16>().len() == col.len()
+        })
+    }
+
     pub fn get_partial(&self, row: usize, col: usize) -> Option<usize> {
         self.values[row * self.n + col].map(|i| i.into())
     }
@@ -32,10 +119,107 @@ impl PartialLatinSquareDyn {
         self.values[row * self.n + col] = val.map(|i| i as u8);
     }
 
+    /// Sets the entry at `row`, `col` to `val` if it does not conflict with another entry
+    #[allow(dead_code)]
+    pub fn try_set(&mut self, row: usize, col: usize, val: usize) -> Result<(), Conflict> {
+        let n = self.n;
+
+        if val >= n {
+            return Err(Conflict::OutOfRange { val });
+        }
+        if let Some(other_col) =
+            (0..n).find(|j| *j != col && self.get_partial(row, *j) == Some(val))
+        {
+            return Err(Conflict::Row {
+                row,
+                col: other_col,
+                val,
+            });
+        }
+        if let Some(other_row) =
+            (0..n).find(|i| *i != row && self.get_partial(*i, col) == Some(val))
+        {
+            return Err(Conflict::Column {
+                row: other_row,
+                col,
+                val,
+            });
+        }
+
+        self.set(row, col, Some(val));
+        Ok(())
+    }
+
+    /// Reconstructs the entries of `full` selected by the little endian mask `bytes`,
+    /// inverse of `to_mask_bytes`
+    pub fn from_mask_bytes(full: &LatinSquareDyn, bytes: &[u8]) -> Self {
+        let bytes_needed = (full.n() * full.n()).div_ceil(8);
+        assert_eq!(bytes.len(), bytes_needed);
+
+        let mut buffer = [0; 16];
+        buffer[0..bytes_needed].copy_from_slice(bytes);
+
+        full.mask(BitSet128::from_bits(u128::from_le_bytes(buffer)))
+    }
+
+    /// Encodes the filled cells as a little endian bitmask, as used by `FindSCS` and `DecodeCS`
+    pub fn to_mask_bytes(&self) -> Vec<u8> {
+        let bytes_needed = (self.n * self.n).div_ceil(8);
+
+        self.filled_bitset().bits().to_le_bytes()[0..bytes_needed].to_vec()
+    }
+
+    /// Returns the cells `i * n + j` which contain an entry
+    pub fn filled_bitset(&self) -> BitSet128 {
+        assert!(self.n * self.n <= 128);
+
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| value.map(|_| i))
+            .collect()
+    }
+
+    /// Returns the cells `i * n + j` which are empty
+    #[allow(dead_code)]
+    pub fn holes_bitset(&self) -> BitSet128 {
+        self.filled_bitset()
+            .complement()
+            .intersect(BitSet128::all_less_than(self.n * self.n))
+    }
+
     pub fn num_entries(&self) -> usize {
         self.values.iter().filter(|v| v.is_some()).count()
     }
 
+    /// Same as `num_entries`
+    pub fn num_filled(&self) -> usize {
+        self.num_entries()
+    }
+
+    /// Returns the fraction of filled cells
+    pub fn density(&self) -> f64 {
+        if self.n == 0 {
+            return 0.0;
+        }
+
+        self.num_entries() as f64 / (self.n * self.n) as f64
+    }
+
+    /// Returns `Some(r)` if the first `r` rows are full, all other rows are empty
+    /// and no value appears twice in a row or column
+    pub fn is_latin_rectangle(&self) -> Option<usize> {
+        let n = self.n;
+
+        let rows = (0..n)
+            .position(|i| (0..n).any(|j| self.get_partial(i, j).is_none()))
+            .unwrap_or(n);
+
+        let rest_empty = (rows..n).all(|i| (0..n).all(|j| self.get_partial(i, j).is_none()));
+
+        (rest_empty && self.is_valid()).then_some(rows)
+    }
+
     pub fn first_empty_index(&self) -> Option<usize> {
         (0..self.n)
             .flat_map(|row| (0..self.n).map(move |col| self.get_partial(row, col)))
@@ -50,12 +234,84 @@ impl PartialLatinSquareDyn {
             .map(|index| index + start)
     }
 
-    pub fn is_uniquely_completable(&self) -> bool {
-        let mut generator = LatinSquareGeneratorDyn::from_partial_sq(self);
-        let first_solution = generator.next();
-        let second_solution = generator.next();
+    /// Returns the first completion found, without searching for any others
+    pub fn first_completion(&self) -> Option<LatinSquareDyn> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        LatinSquareGeneratorDyn::from_partial_sq(self).next()
+    }
+
+    /// Completes a latin rectangle row by row. Each new row is a perfect matching
+    /// between the columns and the symbols not yet used in them, which always exists by Hall's theorem.
+    /// Returns `None` if `self` is not a latin rectangle
+    pub fn complete_rectangle(&self) -> Option<LatinSquareDyn> {
+        let n = self.n;
+        let rows = self.is_latin_rectangle()?;
+
+        let mut sq = self.clone();
+        let mut unused_vals = vec![BitSet16::all_less_than(n); n];
+        for (j, unused) in unused_vals.iter_mut().enumerate() {
+            for i in 0..rows {
+                unused.remove(self.get_partial(i, j).unwrap());
+            }
+        }
+
+        for i in rows..n {
+            // col_of_val[v] is the column v is currently matched to
+            let mut col_of_val = vec![None; n];
 
-        first_solution.is_some() && second_solution.is_none()
+            for j in 0..n {
+                let mut visited = BitSet16::empty();
+                let found = Self::augment(j, &unused_vals, &mut col_of_val, &mut visited);
+                debug_assert!(found);
+            }
+
+            for (val, col) in col_of_val.into_iter().enumerate() {
+                let col = col.unwrap();
+                sq.set(i, col, Some(val));
+                unused_vals[col].remove(val);
+            }
+        }
+
+        let values = sq.values.iter().map(|v| v.unwrap()).collect();
+        LatinSquareDyn::from_boxed_slice(values)
+    }
+
+    /// Tries to find an augmenting path starting at column `col`
+    fn augment(
+        col: usize,
+        unused_vals: &[BitSet16],
+        col_of_val: &mut [Option<usize>],
+        visited: &mut BitSet16,
+    ) -> bool {
+        for val in unused_vals[col] {
+            if visited.contains(val) {
+                continue;
+            }
+            visited.insert(val);
+
+            if col_of_val[val]
+                .is_none_or(|other| Self::augment(other, unused_vals, col_of_val, visited))
+            {
+                col_of_val[val] = Some(col);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns the number of completions, but stops counting at `limit`
+    pub fn num_completions_up_to(&self, limit: usize) -> usize {
+        LatinSquareGeneratorDyn::from_partial_sq(self)
+            .take(limit)
+            .count()
+    }
+
+    pub fn is_uniquely_completable(&self) -> bool {
+        self.num_completions_up_to(2) == 1
     }
 
     pub fn is_uniquely_completable_to(&self, sq: &LatinSquareDyn) -> bool {
@@ -73,18 +329,12 @@ impl PartialLatinSquareDyn {
             return false;
         }
 
-        for i in 0..self.n() {
-            for j in 0..self.n() {
-                if self.get_partial(i, j).is_none() {
-                    continue;
-                }
-
-                let mut copy = self.clone();
-                copy.set(i, j, None);
+        for (i, j) in self.filled_bitset().cells(self.n) {
+            let mut copy = self.clone();
+            copy.set(i, j, None);
 
-                if copy.is_uniquely_completable() {
-                    return false;
-                }
+            if copy.is_uniquely_completable() {
+                return false;
             }
         }
 
@@ -135,6 +385,36 @@ impl PartialLatinSquareDyn {
             *val = permutation.apply(*val as usize) as u8;
         }
     }
+
+    pub fn transpose(&self) -> Self {
+        let n = self.n;
+        let mut sq = Self::empty(n);
+
+        for i in 0..n {
+            for j in 0..n {
+                sq.set(j, i, self.get_partial(i, j));
+            }
+        }
+
+        sq
+    }
+
+    /// Permutes the roles of rows, columns and values of the filled cells
+    pub fn permute_rcs(&self, permutation: &Permutation<3>) -> Self {
+        let n = self.n;
+        let mut sq = Self::empty(n);
+
+        for i in 0..n {
+            for j in 0..n {
+                if let Some(val) = self.get_partial(i, j) {
+                    let [row, col, val] = permutation.apply_array([i, j, val]);
+                    sq.set(row, col, Some(val));
+                }
+            }
+        }
+
+        sq
+    }
 }
 
 impl Display for PartialLatinSquareDyn {
@@ -184,10 +464,73 @@ impl From<&LatinSquareDyn> for PartialLatinSquareDyn {
     }
 }
 
+/// Splits a row of a grid into its fields, separated by commas if there are any or by whitespace otherwise
+pub fn split_grid_row(line: &str) -> Vec<&str> {
+    if line.contains(',') {
+        line.split(',').map(|field| field.trim()).collect()
+    } else {
+        line.split_whitespace().collect()
+    }
+}
+
+/// The reason an entry could not be placed by [`PartialLatinSquareDyn::try_set`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// `val` already appears in the same row at `col`
+    Row {
+        row: usize,
+        col: usize,
+        val: usize,
+    },
+    /// `val` already appears in the same column at `row`
+    Column {
+        row: usize,
+        col: usize,
+        val: usize,
+    },
+    OutOfRange {
+        val: usize,
+    },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::Row { row, col, val } => {
+                write!(f, "Value {val} already appears in row {row} at col {col}")
+            }
+            Conflict::Column { row, col, val } => {
+                write!(f, "Value {val} already appears in col {col} at row {row}")
+            }
+            Conflict::OutOfRange { val } => write!(f, "Value {val} is out of range"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
-    InvalidLength { len: usize },
-    InvalidChar { index: usize, char: char },
+    InvalidLength {
+        len: usize,
+    },
+    InvalidChar {
+        index: usize,
+        char: char,
+    },
+    InvalidRowLength {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    InvalidEntry {
+        row: usize,
+        col: usize,
+        entry: String,
+    },
+    NotALatinSquare,
+    MissingRows {
+        rows: usize,
+        expected: usize,
+    },
 }
 
 impl Display for Error {
@@ -199,6 +542,19 @@ impl Display for Error {
             Error::InvalidChar { index, char } => {
                 write!(f, "Invalid char at index {index}: {char}")
             }
+            Error::InvalidRowLength { row, len, expected } => {
+                write!(f, "Invalid len of row {row}: {len}, expected {expected}")
+            }
+            Error::InvalidEntry { row, col, entry } => {
+                write!(f, "Invalid entry at row {row}, col {col}: {entry}")
+            }
+            Error::MissingRows { rows, expected } => {
+                write!(
+                    f,
+                    "Unexpected end of input after {rows} rows, expected {expected}"
+                )
+            }
+            Error::NotALatinSquare => write!(f, "The latin square property is not met"),
         }
     }
 }
@@ -226,3 +582,275 @@ impl TryFrom<&str> for PartialLatinSquareDyn {
         Ok(PartialLatinSquareDyn { n, values })
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use crate::random_latin_square_generator::RandomLatinSquareGeneratorDyn;
+
+    use super::*;
+
+    #[test]
+    fn mask_bytes() {
+        for n in 1..=11 {
+            for sq in RandomLatinSquareGeneratorDyn::new(n, n as u64).take(3) {
+                let mask = (0..n * n).filter(|i| i % 3 != 1).collect();
+                let partial_sq = sq.mask(mask);
+
+                let bytes = partial_sq.to_mask_bytes();
+                assert_eq!(bytes.len(), (n * n).div_ceil(8));
+                if n >= 2 {
+                    assert_eq!(bytes[0] & 0b111, 0b101);
+                }
+
+                let decoded = PartialLatinSquareDyn::from_mask_bytes(&sq, &bytes);
+                assert_eq!(decoded, partial_sq);
+            }
+        }
+    }
+
+    #[test]
+    fn filled_and_holes_bitset() {
+        for n in 1..=11 {
+            for sq in RandomLatinSquareGeneratorDyn::new(n, n as u64).take(3) {
+                let mask: BitSet128 = (0..n * n).filter(|i| i % 3 != 1).collect();
+                let partial_sq = sq.mask(mask);
+
+                let filled = partial_sq.filled_bitset();
+                let holes = partial_sq.holes_bitset();
+
+                assert_eq!(filled, mask);
+                assert_eq!(filled.len(), partial_sq.num_entries());
+                assert!(filled.is_disjoint(holes));
+                assert_eq!(filled.union(holes), BitSet128::all_less_than(n * n));
+            }
+        }
+
+        assert_eq!(
+            PartialLatinSquareDyn::empty(4).holes_bitset(),
+            BitSet128::all_less_than(16)
+        );
+    }
+
+    #[test]
+    fn try_set() {
+        let mut sq = PartialLatinSquareDyn::try_from("012.1..........2").unwrap();
+
+        assert_eq!(sq.try_set(1, 1, 0), Ok(()));
+        assert_eq!(sq.get_partial(1, 1), Some(0));
+
+        assert_eq!(
+            sq.try_set(1, 2, 0),
+            Err(Conflict::Row {
+                row: 1,
+                col: 1,
+                val: 0
+            })
+        );
+        assert_eq!(
+            sq.try_set(2, 3, 2),
+            Err(Conflict::Column {
+                row: 3,
+                col: 3,
+                val: 2
+            })
+        );
+        assert_eq!(sq.try_set(2, 3, 4), Err(Conflict::OutOfRange { val: 4 }));
+        assert_eq!(sq.get_partial(1, 2), None);
+        assert_eq!(sq.get_partial(2, 3), None);
+
+        // overwriting an entry with itself is not a conflict
+        assert_eq!(sq.try_set(0, 1, 1), Ok(()));
+        assert!(sq.is_valid());
+    }
+
+    #[test]
+    fn first_completion() {
+        let sq = PartialLatinSquareDyn::try_from("012.1..........2").unwrap();
+        assert_eq!(
+            sq.first_completion(),
+            Some(LatinSquareDyn::try_from("0123123023013012").unwrap())
+        );
+
+        let mut sparse = PartialLatinSquareDyn::empty(9);
+        for i in 0..5 {
+            sparse.set(i, 2 * i, Some(i));
+        }
+        let completion = sparse.first_completion().unwrap();
+        for i in 0..5 {
+            assert_eq!(completion.get(i, 2 * i), i);
+        }
+
+        let uncompletable = PartialLatinSquareDyn::try_from("0..1").unwrap();
+        assert_eq!(uncompletable.first_completion(), None);
+
+        let invalid = PartialLatinSquareDyn::try_from("00..").unwrap();
+        assert_eq!(invalid.first_completion(), None);
+    }
+
+    #[test]
+    fn transpose() {
+        let sq = PartialLatinSquareDyn::try_from("01..2.1...3....0").unwrap();
+        let transposed = sq.transpose();
+
+        assert_eq!(transposed.to_string(), "02..1....13....0");
+        assert_eq!(transposed.transpose(), sq);
+        assert_eq!(transposed.num_entries(), sq.num_entries());
+        assert_eq!(
+            sq.permute_rcs(&Permutation::from_array([1, 0, 2])),
+            transposed
+        );
+        assert_eq!(sq.permute_rcs(&Permutation::identity()), sq);
+
+        let full = LatinSquareDyn::try_from("012120201").unwrap();
+        for permutation in crate::permutation::PermutationIter::new() {
+            assert_eq!(
+                PartialLatinSquareDyn::from(&full).permute_rcs(&permutation),
+                PartialLatinSquareDyn::from(&full.permuted_rcs(&permutation))
+            );
+        }
+    }
+
+    #[test]
+    fn is_latin_rectangle() {
+        let rectangle = PartialLatinSquareDyn::try_from("01231032........").unwrap();
+        assert_eq!(rectangle.is_latin_rectangle(), Some(2));
+        assert_eq!(rectangle.num_filled(), 8);
+        assert_eq!(rectangle.density(), 0.5);
+
+        let scattered = PartialLatinSquareDyn::try_from("0..1.2......3...").unwrap();
+        assert_eq!(scattered.is_latin_rectangle(), None);
+        assert_eq!(scattered.num_filled(), 4);
+        assert_eq!(scattered.density(), 0.25);
+
+        let invalid = PartialLatinSquareDyn::try_from("01230123........").unwrap();
+        assert_eq!(invalid.is_latin_rectangle(), None);
+
+        assert_eq!(
+            PartialLatinSquareDyn::empty(4).is_latin_rectangle(),
+            Some(0)
+        );
+        let full = PartialLatinSquareDyn::try_from("0123123023013012").unwrap();
+        assert_eq!(full.is_latin_rectangle(), Some(4));
+    }
+
+    #[test]
+    fn complete_rectangle() {
+        let rectangle = PartialLatinSquareDyn::try_from("012120...").unwrap();
+        assert_eq!(rectangle.complete_rectangle(), rectangle.first_completion());
+        assert_eq!(
+            rectangle.complete_rectangle(),
+            Some(LatinSquareDyn::try_from("012120201").unwrap())
+        );
+
+        for n in 1..=9 {
+            for sq in RandomLatinSquareGeneratorDyn::new(n, n as u64).take(5) {
+                for rows in 0..=n {
+                    let mut partial = PartialLatinSquareDyn::from(&sq);
+                    for i in rows..n {
+                        for j in 0..n {
+                            partial.set(i, j, None);
+                        }
+                    }
+
+                    let completion = partial.complete_rectangle().unwrap();
+                    for i in 0..rows {
+                        for j in 0..n {
+                            assert_eq!(completion.get(i, j), sq.get(i, j));
+                        }
+                    }
+                }
+            }
+        }
+
+        let scattered = PartialLatinSquareDyn::try_from("0..1.2......3...").unwrap();
+        assert_eq!(scattered.complete_rectangle(), None);
+    }
+
+    #[test]
+    fn num_completions_up_to() {
+        let unique = PartialLatinSquareDyn::try_from("012.1..........2").unwrap();
+        assert_eq!(unique.num_completions_up_to(2), 1);
+        assert!(unique.is_uniquely_completable());
+        assert!(unique
+            .is_uniquely_completable_to(&LatinSquareDyn::try_from("0123123023013012").unwrap()));
+
+        let first_row = PartialLatinSquareDyn::try_from("012.............").unwrap();
+        assert_eq!(first_row.num_completions_up_to(2), 2);
+        assert_eq!(first_row.num_completions_up_to(100), 24);
+        assert!(!first_row.is_uniquely_completable());
+
+        assert_eq!(
+            PartialLatinSquareDyn::empty(3).num_completions_up_to(usize::MAX),
+            12
+        );
+        assert_eq!(PartialLatinSquareDyn::empty(3).num_completions_up_to(5), 5);
+        assert_eq!(unique.num_completions_up_to(usize::MAX), 1);
+    }
+
+    #[test]
+    fn parse_line_or_grid() {
+        let input = "01..202.1\n\n0 1 .\n- 2 0\n2 . 1\n0,1,\n,2,0\n2,,1\n";
+        let mut lines = input.lines();
+
+        for _ in 0..3 {
+            let sq = PartialLatinSquareDyn::parse_line_or_grid(&mut lines)
+                .unwrap()
+                .unwrap();
+            assert_eq!(sq.to_string(), "01..202.1");
+        }
+        assert!(PartialLatinSquareDyn::parse_line_or_grid(&mut lines).is_none());
+    }
+
+    #[test]
+    fn parse_line_or_grid_malformed() {
+        let mut lines = "0 1 2\n120\n2 0 1".lines();
+        assert!(matches!(
+            PartialLatinSquareDyn::parse_line_or_grid(&mut lines),
+            Some(Err(Error::InvalidRowLength { row: 1, .. }))
+        ));
+
+        let mut lines = "0 1 2\n1 2 0".lines();
+        assert!(matches!(
+            PartialLatinSquareDyn::parse_line_or_grid(&mut lines),
+            Some(Err(Error::MissingRows {
+                rows: 2,
+                expected: 3
+            }))
+        ));
+
+        let mut lines = "01.2".lines();
+        assert!(matches!(
+            PartialLatinSquareDyn::parse_line_or_grid(&mut lines),
+            Some(Err(Error::InvalidChar { .. }))
+        ));
+    }
+
+    #[test]
+    fn from_grid_whitespace() {
+        let sq = PartialLatinSquareDyn::from_grid(&["0 1 .", "- 2 0", "2 . 1"]).unwrap();
+        assert_eq!(sq.to_string(), "01..202.1");
+    }
+
+    #[test]
+    fn from_grid_comma() {
+        let sq = PartialLatinSquareDyn::from_grid(&["0,1,", " , 2, 0", "2,.,1"]).unwrap();
+        assert_eq!(sq.to_string(), "01..202.1");
+    }
+
+    #[test]
+    fn from_grid_invalid() {
+        assert!(matches!(
+            PartialLatinSquareDyn::from_grid(&["0 1", "1"]),
+            Err(Error::InvalidRowLength { row: 1, .. })
+        ));
+        assert!(matches!(
+            PartialLatinSquareDyn::from_grid(&["0 2", "1 0"]),
+            Err(Error::InvalidEntry { row: 0, col: 1, .. })
+        ));
+        assert!(matches!(
+            PartialLatinSquareDyn::from_grid(&["0 0", ". ."]),
+            Err(Error::NotALatinSquare)
+        ));
+    }
+}
@@ -1,6 +1,9 @@
 use std::mem::MaybeUninit;
 
-use crate::{cycles::CYCLE_STRUCTURES, permutation_simd::PermutationSimd};
+use crate::{
+    bitset::BitSet16, cycles::CYCLE_STRUCTURES, permutation_dyn::PermutationDyn,
+    permutation_simd::PermutationSimd,
+};
 
 pub const fn factorial(n: usize) -> usize {
     let mut i = 2;
@@ -59,6 +62,64 @@ impl<const N: usize> Permutation<N> {
         Permutation::from_array(array)
     }
 
+    /// Extends `permutation` to N elements, fixing all indices `>= permutation.len()`
+    pub fn from_dyn_padded(permutation: &PermutationDyn) -> Self {
+        assert!(permutation.len() <= N);
+
+        let mut elements = Self::identity().0;
+        elements[..permutation.len()].copy_from_slice(permutation.as_vec());
+
+        Permutation::from_array(elements)
+    }
+
+    /// Same as `from_dyn_padded` for a smaller const permutation
+    #[allow(dead_code)]
+    pub fn from_smaller<const M: usize>(permutation: Permutation<M>) -> Self {
+        assert!(M <= N);
+
+        let mut elements = Self::identity().0;
+        elements[..M].copy_from_slice(&permutation.0);
+
+        Permutation::from_array(elements)
+    }
+
+    /// Returns a random permutation using a Fisher-Yates shuffle driven by `rng`,
+    /// same as `PermutationDyn::random`
+    pub fn random(rng: &mut impl FnMut() -> u64) -> Self {
+        Self::from_dyn_padded(&PermutationDyn::random(N, rng))
+    }
+
+    /// Returns the permutation with the given rank in lexicographic order,
+    /// the rank is decoded as a Lehmer code
+    pub fn from_rank(mut rank: usize) -> Self {
+        debug_assert!(rank < FACTORIAL[N]);
+
+        let mut elements = [0; N];
+        let mut elements_left = BitSet16::all_less_than(N);
+
+        for (k, element) in elements.iter_mut().enumerate() {
+            let fac = FACTORIAL[N - k - 1];
+            *element = elements_left.select(rank / fac).unwrap();
+            elements_left.remove(*element);
+            rank %= fac;
+        }
+
+        Permutation(elements)
+    }
+
+    /// Inverse of `from_rank`
+    pub fn to_rank(&self) -> usize {
+        let mut rank = 0;
+        let mut elements_left = BitSet16::all_less_than(N);
+
+        for (k, element) in self.0.iter().enumerate() {
+            rank += elements_left.rank(*element) * FACTORIAL[N - k - 1];
+            elements_left.remove(*element);
+        }
+
+        rank
+    }
+
     pub fn into_array(self) -> [usize; N] {
         self.0
     }
@@ -85,6 +146,16 @@ impl<const N: usize> Permutation<N> {
         Self::from_array(inverse)
     }
 
+    /// Returns the permutation that applies `other` and then `self`
+    pub fn compose(&self, other: &Self) -> Self {
+        Permutation(other.0.map(|i| self.0[i]))
+    }
+
+    /// Returns the permutation that applies `self` and then `other`
+    pub fn then(&self, other: &Self) -> Self {
+        other.compose(self)
+    }
+
     pub fn cycles(&self) -> Vec<Vec<usize>> {
         let mut cycles = Vec::with_capacity((N + 1) / 2);
         let mut used = [false; N];
@@ -136,6 +207,29 @@ impl<const N: usize> Permutation<N> {
         cycles
     }
 
+    /// Checks whether `i -> (self(i) - i) mod N` is a permutation too,
+    /// i.e. whether `self` is a complete mapping of the cyclic group of order N
+    pub fn is_complete_mapping(&self) -> bool {
+        let differences: BitSet16 = (0..N).map(|i| (self.apply(i) + N - i) % N).collect();
+
+        differences.len() == N
+    }
+
+    /// Returns 1 for even and -1 for odd permutations
+    pub fn sign(&self) -> i8 {
+        let transpositions: usize = self.cycle_lengths().iter().map(|len| len - 1).sum();
+
+        if transpositions.is_multiple_of(2) {
+            1
+        } else {
+            -1
+        }
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.sign() == 1
+    }
+
     pub fn cycle_lengths_index(&self) -> usize {
         let mut cycles = [0; N];
         let mut cycle_count = 0;
@@ -207,6 +301,18 @@ impl<const N: usize> Permutation<N> {
     }
 }
 
+impl Permutation<3> {
+    /// Parses the name of a conjugate, which is the order of rows, columns and symbols
+    /// after applying the permutation with `apply_array`, e.g. `"CRS"` for the transpose
+    pub fn from_rcs_name(name: &str) -> Option<Self> {
+        PermutationIter::new().find(|rcs: &Self| {
+            rcs.apply_array(['R', 'C', 'S'])
+                .into_iter()
+                .eq(name.chars().map(|c| c.to_ascii_uppercase()))
+        })
+    }
+}
+
 impl<const N: usize> From<[usize; N]> for Permutation<N> {
     fn from(value: [usize; N]) -> Self {
         Permutation::from_array(value)
@@ -281,8 +387,81 @@ impl<const N: usize> ExactSizeIterator for PermutationIter<N> {
 #[cfg(test)]
 mod test {
 
+    use crate::random_latin_square_generator::RandomLatinSquareGeneratorDyn;
+
     use super::*;
 
+    #[test]
+    fn padded() {
+        let small = Permutation::from_array([2, 0, 1]);
+        let padded = Permutation::<5>::from_smaller(small.clone());
+
+        assert_eq!(padded, Permutation::from_array([2, 0, 1, 3, 4]));
+        assert_eq!(
+            Permutation::<5>::from_dyn_padded(&PermutationDyn::from_array([2, 0, 1])),
+            padded
+        );
+        assert_eq!(Permutation::<3>::from_smaller(small.clone()), small);
+        assert_eq!(
+            Permutation::<4>::from_dyn_padded(&PermutationDyn::identity(0)),
+            Permutation::identity()
+        );
+    }
+
+    #[test]
+    fn is_complete_mapping() {
+        assert!(Permutation::<1>::identity().is_complete_mapping());
+        assert!(Permutation::from_array([0, 2, 1]).is_complete_mapping());
+        assert!(Permutation::from_array([0, 2, 4, 1, 3]).is_complete_mapping());
+        assert!(!Permutation::<5>::identity().is_complete_mapping());
+
+        // cyclic groups of even order have no complete mappings
+        assert!(!PermutationIter::<2>::new().any(|p| p.is_complete_mapping()));
+        assert!(!PermutationIter::<4>::new().any(|p| p.is_complete_mapping()));
+        assert!(!PermutationIter::<6>::new().any(|p| p.is_complete_mapping()));
+
+        assert_eq!(
+            PermutationIter::<5>::new()
+                .filter(|p| p.is_complete_mapping())
+                .count(),
+            15
+        );
+    }
+
+    #[test]
+    fn random() {
+        let rng = |seed| {
+            let mut state = [seed, 1, 2, 3];
+            for _ in 0..100 {
+                state = RandomLatinSquareGeneratorDyn::xoshiro(state).1;
+            }
+            move || {
+                let (result, next_state) = RandomLatinSquareGeneratorDyn::xoshiro(state);
+                state = next_state;
+                result
+            }
+        };
+
+        for seed in 0..20 {
+            let permutation = Permutation::<7>::random(&mut rng(seed));
+            assert_eq!(permutation, Permutation::<7>::random(&mut rng(seed)));
+
+            let mut elements = *permutation.as_array();
+            elements.sort();
+            assert_eq!(elements, *Permutation::<7>::identity().as_array());
+
+            assert_eq!(
+                PermutationDyn::random(7, &mut rng(seed)).into_vec(),
+                permutation.as_array().to_vec()
+            );
+        }
+
+        let permutations: Vec<_> = (0..20)
+            .map(|seed| Permutation::<7>::random(&mut rng(seed)))
+            .collect();
+        assert!(permutations.iter().any(|p| *p != permutations[0]));
+    }
+
     #[test]
     fn inverse_test() {
         let permutation = Permutation::from_array([3, 1, 4, 2, 0]);
@@ -303,6 +482,62 @@ mod test {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn rank_test() {
+        for (rank, permutation) in PermutationIter::<4>::new().enumerate() {
+            assert_eq!(Permutation::<4>::from_rank(rank), permutation);
+            assert_eq!(permutation.to_rank(), rank);
+        }
+
+        assert_eq!(Permutation::<4>::from_rank(23), Permutation([3, 2, 1, 0]));
+
+        for rank in [0, 1, 1000, 5039] {
+            assert_eq!(Permutation::<7>::from_rank(rank).to_rank(), rank);
+        }
+    }
+
+    #[test]
+    fn sign_test() {
+        assert_eq!(Permutation::<5>::identity().sign(), 1);
+        assert!(Permutation::<5>::identity().is_even());
+
+        let transposition = Permutation::from_array([1, 0, 2, 3, 4]);
+        assert_eq!(transposition.sign(), -1);
+        assert!(!transposition.is_even());
+
+        let three_cycle = Permutation::from_array([1, 2, 0, 3, 4]);
+        assert_eq!(three_cycle.sign(), 1);
+        assert!(three_cycle.is_even());
+
+        for p in PermutationIter::<4>::new() {
+            for q in PermutationIter::<4>::new() {
+                assert_eq!(p.compose(&q).sign(), p.sign() * q.sign());
+            }
+        }
+    }
+
+    #[test]
+    fn compose_test() {
+        let p = Permutation::from_array([1, 2, 0, 3]);
+        let q = Permutation::from_array([0, 1, 3, 2]);
+
+        assert_eq!(p.compose(&q), Permutation([1, 2, 3, 0]));
+        assert_eq!(p.then(&q), Permutation([1, 3, 0, 2]));
+        assert_eq!(p.compose(&q).apply(2), p.apply(q.apply(2)));
+        assert_eq!(p.compose(&p.inverse()), Permutation::identity());
+        assert_eq!(p.inverse().compose(&p), Permutation::identity());
+
+        let permutations: Vec<_> = PermutationIter::<4>::new().collect();
+        for a in &permutations {
+            for b in &permutations {
+                assert_eq!(a.then(b), b.compose(a));
+                for c in permutations.iter().step_by(5) {
+                    assert_eq!(a.compose(&b.compose(c)), a.compose(b).compose(c));
+                }
+            }
+        }
+    }
+
     #[test]
     fn cycle_test() {
         assert_eq!(
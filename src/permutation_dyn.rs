@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use crate::permutation::{Permutation, FACTORIAL};
 
 /// A permutation of elements
@@ -13,6 +15,18 @@ impl PermutationDyn {
         PermutationDyn(elements)
     }
 
+    /// Returns a random permutation of `n` elements using a Fisher-Yates shuffle driven by `rng`
+    pub fn random(n: usize, rng: &mut impl FnMut() -> u64) -> Self {
+        let mut elements = Self::identity(n).0;
+
+        for i in (1..n).rev() {
+            let j = (rng() % (i as u64 + 1)) as usize;
+            elements.swap(i, j);
+        }
+
+        PermutationDyn(elements)
+    }
+
     pub fn from_rank(mut rank: usize, n: usize) -> Self {
         let mut permutation = vec![0; n];
         let mut elements_left = vec![None; n];
@@ -37,6 +51,23 @@ impl PermutationDyn {
         PermutationDyn(permutation)
     }
 
+    /// Inverse of `from_rank`
+    pub fn to_rank(&self) -> usize {
+        let n = self.0.len();
+        let mut rank = 0;
+
+        for (k, element) in self.0.iter().enumerate() {
+            let smaller = self.0[k + 1..].iter().filter(|e| *e < element).count();
+            rank += smaller * FACTORIAL[n - k - 1];
+        }
+
+        rank
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
     pub fn from_array<const N: usize>(elements: [usize; N]) -> Self {
         for i in 0..N {
             debug_assert!(elements.contains(&i));
@@ -83,6 +114,31 @@ impl PermutationDyn {
     pub fn apply(&self, num: usize) -> usize {
         self.0[num]
     }
+
+    pub fn cycle_lengths(&self) -> Vec<usize> {
+        let mut cycles = Vec::new();
+        let mut used = vec![false; self.0.len()];
+
+        for start in 0..self.0.len() {
+            if used[start] {
+                continue;
+            }
+
+            used[start] = true;
+            let mut cycle_len = 1;
+            let mut current = self.0[start];
+
+            while current != start {
+                used[current] = true;
+                cycle_len += 1;
+                current = self.0[current];
+            }
+
+            cycles.push(cycle_len);
+        }
+
+        cycles
+    }
 }
 
 impl<const N: usize> From<&PermutationDyn> for Permutation<N> {
@@ -94,6 +150,53 @@ impl<const N: usize> From<&PermutationDyn> for Permutation<N> {
     }
 }
 
+#[derive(Debug)]
+pub enum Error {
+    InvalidEntry { index: usize, entry: String },
+    NotAPermutation,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidEntry { index, entry } => {
+                write!(f, "Invalid entry at index {index}: {entry}")
+            }
+            Error::NotAPermutation => write!(f, "The entries are not a permutation"),
+        }
+    }
+}
+
+/// Parses a comma separated list of the images of `0..n`
+impl TryFrom<&str> for PermutationDyn {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut elements = Vec::new();
+
+        for (index, entry) in value.split(',').enumerate() {
+            let Ok(element) = entry.trim().parse::<usize>() else {
+                return Err(Error::InvalidEntry {
+                    index,
+                    entry: entry.to_string(),
+                });
+            };
+            elements.push(element);
+        }
+
+        let n = elements.len();
+        let mut seen = vec![false; n];
+        for element in &elements {
+            if *element >= n || seen[*element] {
+                return Err(Error::NotAPermutation);
+            }
+            seen[*element] = true;
+        }
+
+        Ok(PermutationDyn(elements))
+    }
+}
+
 #[derive(Debug, Clone)]
 
 pub struct PermutationDynIter {
@@ -167,10 +270,45 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn try_from_str() {
+        let permutation = PermutationDyn::try_from("2,0, 3,1").unwrap();
+        assert_eq!(permutation, PermutationDyn::from_array([2, 0, 3, 1]));
+
+        assert!(matches!(
+            PermutationDyn::try_from("0,x,1"),
+            Err(Error::InvalidEntry { index: 1, .. })
+        ));
+        assert!(matches!(
+            PermutationDyn::try_from("0,0,1"),
+            Err(Error::NotAPermutation)
+        ));
+        assert!(matches!(
+            PermutationDyn::try_from("0,3,1"),
+            Err(Error::NotAPermutation)
+        ));
+    }
+
     #[test]
     fn single_iter() {
         let mut iter = PermutationDynIter::new(1);
         assert_eq!(iter.next(), Some(PermutationDyn::from_array([0])));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn rank() {
+        for rank in 0..FACTORIAL[5] {
+            let permutation = PermutationDyn::from_rank(rank, 5);
+            assert_eq!(permutation.to_rank(), rank);
+            assert_eq!(
+                PermutationDyn::from_rank(permutation.to_rank(), permutation.len()),
+                permutation
+            );
+        }
+
+        for (rank, permutation) in PermutationDynIter::new(4).enumerate() {
+            assert_eq!(permutation.to_rank(), rank);
+        }
+    }
 }
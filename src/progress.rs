@@ -0,0 +1,17 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Prints a progress message to stderr if `--verbose` was given
+pub fn progress(msg: impl Display) {
+    if VERBOSE.load(Ordering::Relaxed) {
+        eprintln!("{msg}");
+    }
+}
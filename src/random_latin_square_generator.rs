@@ -1,5 +1,9 @@
 use crate::{constraints::ConstraintsDyn, latin_square_dyn::LatinSquareDyn};
 
+/// Generates pseudo-random latin squares by randomized backtracking.
+///
+/// The randomness comes from a xoshiro256** generator whose state is initialized
+/// to `[seed, 1, 2, 3]`, so the sequence of squares only depends on `n` and `seed`.
 pub struct RandomLatinSquareGeneratorDyn {
     n: usize,
     random_state: [u64; 4],
@@ -13,8 +17,14 @@ impl RandomLatinSquareGeneratorDyn {
         }
     }
 
+    /// Returns the square at position `index` of the sequence generated for `n` and `seed`
+    #[allow(dead_code)]
+    pub fn nth_square(n: usize, seed: u64, index: usize) -> LatinSquareDyn {
+        Self::new(n, seed).nth(index).unwrap()
+    }
+
     /// https://en.wikipedia.org/wiki/Xorshift#xoshiro256**
-    fn xoshiro(state: [u64; 4]) -> (u64, [u64; 4]) {
+    pub fn xoshiro(state: [u64; 4]) -> (u64, [u64; 4]) {
         let result = state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
 
         let new_state = [
@@ -50,8 +60,7 @@ impl Iterator for RandomLatinSquareGeneratorDyn {
             let values = constraints.get_possibilities(i, j);
 
             if values.is_empty() {
-                dbg!(constraints, i, j);
-                unreachable!()
+                unreachable!("no values possible at ({i}, {j}) in {constraints:?}")
             }
 
             let value = values
@@ -95,3 +104,21 @@ impl Iterator for RandomLatinSquareGeneratorDyn {
         unreachable!()
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn nth_square() {
+        for n in 1..=7 {
+            let sqs: Vec<_> = RandomLatinSquareGeneratorDyn::new(n, 42).take(5).collect();
+
+            for (index, sq) in sqs.iter().enumerate() {
+                assert_eq!(&RandomLatinSquareGeneratorDyn::nth_square(n, 42, index), sq);
+                assert_eq!(&RandomLatinSquareGeneratorDyn::nth_square(n, 42, index), sq);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Read, StdinLock, StdoutLock, Write},
+    path::Path,
+    sync::{Mutex, MutexGuard, OnceLock, ReentrantLock, ReentrantLockGuard},
+};
+
+static INPUT: OnceLock<Mutex<BufReader<File>>> = OnceLock::new();
+static OUTPUT: OnceLock<ReentrantLock<RefCell<BufWriter<File>>>> = OnceLock::new();
+
+/// Reads from `path` instead of stdin
+pub fn set_input(path: &Path) -> io::Result<()> {
+    let file = File::open(path)?;
+    INPUT
+        .set(Mutex::new(BufReader::new(file)))
+        .map_err(|_| io::Error::other("input was already set"))
+}
+
+/// Writes to `path` instead of stdout
+pub fn set_output(path: &Path) -> io::Result<()> {
+    let file = File::create(path)?;
+    OUTPUT
+        .set(ReentrantLock::new(RefCell::new(BufWriter::new(file))))
+        .map_err(|_| io::Error::other("output was already set"))
+}
+
+/// Flushes the output file, needs to be called before exiting
+pub fn flush() -> io::Result<()> {
+    match OUTPUT.get() {
+        Some(output) => output.lock().borrow_mut().flush(),
+        None => stdout().flush(),
+    }
+}
+
+/// Returns the input given by `--input`, or a locked stdin
+pub fn input() -> Input {
+    match INPUT.get() {
+        Some(input) => Input::File(input.lock().unwrap()),
+        None => Input::Stdin(stdin().lock()),
+    }
+}
+
+/// Returns the output given by `--output`, or a locked stdout
+pub fn output() -> Output {
+    match OUTPUT.get() {
+        Some(output) => Output::File(output.lock()),
+        None => Output::Stdout(stdout().lock()),
+    }
+}
+
+pub enum Input {
+    Stdin(StdinLock<'static>),
+    File(MutexGuard<'static, BufReader<File>>),
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Stdin(stdin) => stdin.read(buf),
+            Input::File(file) => file.read(buf),
+        }
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Input::Stdin(stdin) => stdin.fill_buf(),
+            Input::File(file) => file.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Input::Stdin(stdin) => stdin.consume(amt),
+            Input::File(file) => file.consume(amt),
+        }
+    }
+}
+
+/// Like the stdout lock, the output file stays locked while the `Output` is alive,
+/// so the lines written through it are not interleaved with other threads.
+/// The lock is reentrant, so nested calls to `output` don't deadlock
+pub enum Output {
+    Stdout(StdoutLock<'static>),
+    File(ReentrantLockGuard<'static, RefCell<BufWriter<File>>>),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::File(file) => file.borrow_mut().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::File(file) => file.borrow_mut().flush(),
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use std::{
-    io::{stdout, Write},
+    collections::BTreeMap,
+    io::Write,
     thread::{self, JoinHandle},
     time::Duration,
 };
 
 use crate::{
-    cycles::{generate_minimize_rows_lookup_simd, PermutationSimdLookup},
+    cycles::{
+        generate_minimize_rows_lookup, generate_minimize_rows_lookup_simd, PermutationLookup,
+        PermutationSimdLookup,
+    },
     isotopy_class_generator::RowGenerator,
     latin_square::LatinSquare,
+    redirect::output,
     row_partial_latin_square::RowPartialLatinSquare,
 };
 
@@ -15,7 +20,6 @@ use crate::{
 pub struct ThreadedMainClassGenerator<'a, const N: usize> {
     row_generators: Vec<RowGenerator<'a, N>>,
     lookup: &'a PermutationSimdLookup,
-    threads: Vec<JoinHandle<()>>,
 }
 
 impl<'a, const N: usize> ThreadedMainClassGenerator<'a, N> {
@@ -26,11 +30,27 @@ impl<'a, const N: usize> ThreadedMainClassGenerator<'a, N> {
                 lookup,
             )],
             lookup,
-            threads: Vec::new(),
         }
     }
 
-    pub fn run(mut self, max_threads: usize) {
+    pub fn run(self, max_threads: usize) {
+        self.run_with::<PrintMainClasses<N>>(max_threads);
+    }
+
+    /// Same as `run`, but checks every representative against `main_class_permutation`
+    pub fn run_verified(self, max_threads: usize) {
+        self.run_with::<VerifyMainClasses<N, PrintMainClasses<N>>>(max_threads);
+    }
+
+    /// Returns the number of main classes by number of intercalates
+    pub fn count(self, max_threads: usize) -> BTreeMap<usize, usize> {
+        self.run_with::<CountMainClasses<N>>(max_threads).counts
+    }
+
+    fn run_with<V: MainClassVisitor<N>>(mut self, max_threads: usize) -> V {
+        let mut visitor = V::default();
+        let mut threads: Vec<JoinHandle<V>> = Vec::new();
+
         while let Some(generator) = self.row_generators.last_mut() {
             let Some(sq) = generator.next() else {
                 self.row_generators.pop();
@@ -40,8 +60,7 @@ impl<'a, const N: usize> ThreadedMainClassGenerator<'a, N> {
             if sq.is_complete() && sq.is_minimal_main_class(self.lookup) {
                 let sq: LatinSquare<N> = sq.try_into().unwrap();
 
-                let mut stdout = stdout();
-                writeln!(stdout, "{sq}").unwrap();
+                visitor.visit(sq);
 
                 continue;
             }
@@ -49,34 +68,37 @@ impl<'a, const N: usize> ThreadedMainClassGenerator<'a, N> {
             if self.row_generators.len() <= 2 || max_threads == 1 {
                 self.row_generators.push(RowGenerator::new(sq, self.lookup));
             } else {
-                while self.threads.len() >= max_threads {
-                    for i in 0..self.threads.len() {
-                        if !self.threads[i].is_finished() {
+                while threads.len() >= max_threads {
+                    for i in 0..threads.len() {
+                        if !threads[i].is_finished() {
                             continue;
                         }
 
-                        let thread = self.threads.swap_remove(i);
-                        thread.join().unwrap();
+                        let thread = threads.swap_remove(i);
+                        visitor.merge(thread.join().unwrap());
                         break;
                     }
                     thread::sleep(Duration::from_micros(10));
                 }
 
                 let thread = thread::spawn(|| Self::run_thread(sq));
-                self.threads.push(thread);
+                threads.push(thread);
             }
         }
 
-        for thread in self.threads {
-            thread.join().unwrap();
+        for thread in threads {
+            visitor.merge(thread.join().unwrap());
         }
+        visitor.finish();
+
+        visitor
     }
 
-    fn run_thread(sq: RowPartialLatinSquare<N>) {
+    fn run_thread<V: MainClassVisitor<N>>(sq: RowPartialLatinSquare<N>) -> V {
         let lookup_simd = &generate_minimize_rows_lookup_simd::<N>();
 
         let mut row_generators = vec![RowGenerator::new(sq, lookup_simd)];
-        let mut sqs = Vec::with_capacity(1000);
+        let mut visitor = V::default();
 
         while let Some(generator) = row_generators.last_mut() {
             let Some(sq) = generator.next() else {
@@ -87,15 +109,9 @@ impl<'a, const N: usize> ThreadedMainClassGenerator<'a, N> {
             if sq.is_complete() && sq.is_minimal_main_class(lookup_simd) {
                 let sq: LatinSquare<N> = sq.try_into().unwrap();
 
-                sqs.push(sq);
-
-                if sqs.len() >= 1000 {
-                    let mut stdout = stdout().lock();
-                    for sq in sqs.drain(..) {
-                        if writeln!(stdout, "{sq}").is_err() {
-                            return;
-                        }
-                    }
+                visitor.visit(sq);
+                if visitor.is_done() {
+                    break;
                 }
 
                 continue;
@@ -104,11 +120,158 @@ impl<'a, const N: usize> ThreadedMainClassGenerator<'a, N> {
             row_generators.push(RowGenerator::new(sq, lookup_simd));
         }
 
-        let mut stdout = stdout().lock();
-        for sq in sqs.drain(..) {
+        visitor.finish();
+
+        visitor
+    }
+}
+
+/// Receives the main class representatives found by each thread
+trait MainClassVisitor<const N: usize>: Default + Send + 'static {
+    fn visit(&mut self, sq: LatinSquare<N>);
+
+    /// Combines the results of a finished thread into `self`
+    fn merge(&mut self, _other: Self) {}
+
+    fn finish(&mut self) {}
+
+    fn is_done(&self) -> bool {
+        false
+    }
+}
+
+/// Writes the representatives to stdout in batches
+struct PrintMainClasses<const N: usize> {
+    sqs: Vec<LatinSquare<N>>,
+    closed: bool,
+}
+
+impl<const N: usize> Default for PrintMainClasses<N> {
+    fn default() -> Self {
+        PrintMainClasses {
+            sqs: Vec::with_capacity(1000),
+            closed: false,
+        }
+    }
+}
+
+impl<const N: usize> MainClassVisitor<N> for PrintMainClasses<N> {
+    fn visit(&mut self, sq: LatinSquare<N>) {
+        self.sqs.push(sq);
+
+        if self.sqs.len() >= 1000 {
+            self.finish();
+        }
+    }
+
+    fn finish(&mut self) {
+        let mut stdout = output();
+        for sq in self.sqs.drain(..) {
             if writeln!(stdout, "{sq}").is_err() {
+                self.closed = true;
                 return;
             }
         }
     }
+
+    fn is_done(&self) -> bool {
+        self.closed
+    }
+}
+
+/// Counts the representatives by their number of intercalates
+struct CountMainClasses<const N: usize> {
+    counts: BTreeMap<usize, usize>,
+}
+
+impl<const N: usize> Default for CountMainClasses<N> {
+    fn default() -> Self {
+        CountMainClasses {
+            counts: BTreeMap::new(),
+        }
+    }
+}
+
+impl<const N: usize> MainClassVisitor<N> for CountMainClasses<N> {
+    fn visit(&mut self, sq: LatinSquare<N>) {
+        *self.counts.entry(sq.num_intercalates()).or_insert(0) += 1;
+    }
+
+    fn merge(&mut self, other: Self) {
+        for (intercalates, count) in other.counts {
+            *self.counts.entry(intercalates).or_insert(0) += count;
+        }
+    }
+}
+
+/// Panics if `main_class_lookup` and `main_class_permutation` disagree on a representative,
+/// otherwise passes it on to `V`
+struct VerifyMainClasses<const N: usize, V: MainClassVisitor<N>> {
+    visitor: V,
+    lookup: PermutationLookup<N>,
+}
+
+impl<const N: usize, V: MainClassVisitor<N>> Default for VerifyMainClasses<N, V> {
+    fn default() -> Self {
+        VerifyMainClasses {
+            visitor: V::default(),
+            lookup: generate_minimize_rows_lookup(),
+        }
+    }
+}
+
+impl<const N: usize, V: MainClassVisitor<N>> MainClassVisitor<N> for VerifyMainClasses<N, V> {
+    fn visit(&mut self, sq: LatinSquare<N>) {
+        let fast = sq.main_class_lookup(&self.lookup);
+        let slow = sq.main_class_permutation().0;
+        assert_eq!(
+            fast, slow,
+            "main_class_lookup disagrees with main_class_permutation for {sq}"
+        );
+
+        self.visitor.visit(sq);
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.visitor.merge(other.visitor);
+    }
+
+    fn finish(&mut self) {
+        self.visitor.finish();
+    }
+
+    fn is_done(&self) -> bool {
+        self.visitor.is_done()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn verify_main_classes() {
+        let lookup = generate_minimize_rows_lookup_simd::<5>();
+        let counts = ThreadedMainClassGenerator::<5>::new(&lookup)
+            .run_with::<VerifyMainClasses<5, CountMainClasses<5>>>(1)
+            .visitor
+            .counts;
+        assert_eq!(counts.values().sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn count_main_classes() {
+        let lookup = generate_minimize_rows_lookup_simd::<5>();
+        let counts = ThreadedMainClassGenerator::<5>::new(&lookup).count(1);
+        assert_eq!(counts.values().sum::<usize>(), 2);
+
+        let lookup = generate_minimize_rows_lookup_simd::<6>();
+        let counts = ThreadedMainClassGenerator::<6>::new(&lookup).count(1);
+        assert_eq!(counts.values().sum::<usize>(), 12);
+        assert_eq!(
+            ThreadedMainClassGenerator::<6>::new(&lookup).count(4),
+            counts
+        );
+    }
 }
@@ -72,13 +72,24 @@ impl TupleIteratorDyn {
     }
 }
 
-impl Iterator for TupleIteratorDyn {
-    type Item = Box<[usize]>;
+impl TupleIteratorDyn {
+    /// Writes the next tuple into `buffer` instead of allocating a new one.
+    /// Returns `false` if there are no tuples left
+    pub fn next_into(&mut self, buffer: &mut [usize]) -> bool {
+        let Some(current) = self.current.as_ref() else {
+            return false;
+        };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let current = self.current.as_mut()?;
+        buffer.copy_from_slice(current);
+        self.advance();
 
-        let prev = current.clone();
+        true
+    }
+
+    fn advance(&mut self) {
+        let Some(current) = self.current.as_mut() else {
+            return;
+        };
 
         if current.first().is_some_and(|v| *v == self.n - self.k) {
             self.current = None;
@@ -94,6 +105,15 @@ impl Iterator for TupleIteratorDyn {
                 }
             }
         }
+    }
+}
+
+impl Iterator for TupleIteratorDyn {
+    type Item = Box<[usize]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev = self.current.clone()?;
+        self.advance();
 
         Some(prev)
     }
@@ -125,6 +145,31 @@ mod test {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn next_into() {
+        for n in 1..8 {
+            for k in 1..=n {
+                let expected: Vec<_> = TupleIteratorDyn::new(n, k).collect();
+
+                let mut iter = TupleIteratorDyn::new(n, k);
+                let mut buffer = vec![0; k];
+                let mut tuples = Vec::new();
+                while iter.next_into(&mut buffer) {
+                    tuples.push(buffer.clone().into_boxed_slice());
+                }
+
+                assert_eq!(tuples, expected);
+                if k == 3 {
+                    let expected: Vec<_> = TupleIterator::<3>::new(n).collect();
+                    assert!(tuples
+                        .iter()
+                        .map(|t| &t[..])
+                        .eq(expected.iter().map(|t| &t[..])));
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_3_2() {
         let mut iter = TupleIterator::new(3);
@@ -0,0 +1,182 @@
+use crate::{
+    latin_square_dyn::LatinSquareDyn, random_latin_square_generator::RandomLatinSquareGeneratorDyn,
+};
+
+/// Generates uniformly distributed random latin squares using the Markov chain of Jacobson and Matthews
+pub struct UniformLatinSquareGenerator {
+    cube: IncidenceCube,
+    mixing_steps: usize,
+    random_state: [u64; 4],
+}
+
+impl UniformLatinSquareGenerator {
+    /// Starts the chain at the cyclic square, each sample is taken after at least
+    /// `mixing_steps` moves. About `n^3` moves are enough for the chain to mix
+    pub fn new(n: usize, seed: u64, mixing_steps: usize) -> Self {
+        assert!(n > 0);
+
+        UniformLatinSquareGenerator {
+            cube: IncidenceCube::cyclic(n),
+            mixing_steps,
+            random_state: [seed, 1, 2, 3],
+        }
+    }
+
+    fn next_random(&mut self, max: usize) -> usize {
+        let (result, next_state) = RandomLatinSquareGeneratorDyn::xoshiro(self.random_state);
+        self.random_state = next_state;
+        result as usize % max
+    }
+
+    fn step(&mut self) {
+        let n = self.cube.n;
+
+        let (r, c, s, r1, c1, s1) = match self.cube.improper_cell {
+            None => {
+                let (r, c, s) = loop {
+                    let cell = (
+                        self.next_random(n),
+                        self.next_random(n),
+                        self.next_random(n),
+                    );
+                    if self.cube.get(cell.0, cell.1, cell.2) == 0 {
+                        break cell;
+                    }
+                };
+
+                let r1 = (0..n).find(|i| self.cube.get(*i, c, s) == 1).unwrap();
+                let c1 = (0..n).find(|i| self.cube.get(r, *i, s) == 1).unwrap();
+                let s1 = (0..n).find(|i| self.cube.get(r, c, *i) == 1).unwrap();
+
+                (r, c, s, r1, c1, s1)
+            }
+            Some((r, c, s)) => {
+                // each line through the improper cell contains two ones, choose one of them
+                let rows: Vec<_> = (0..n).filter(|i| self.cube.get(*i, c, s) == 1).collect();
+                let cols: Vec<_> = (0..n).filter(|i| self.cube.get(r, *i, s) == 1).collect();
+                let vals: Vec<_> = (0..n).filter(|i| self.cube.get(r, c, *i) == 1).collect();
+
+                let r1 = rows[self.next_random(2)];
+                let c1 = cols[self.next_random(2)];
+                let s1 = vals[self.next_random(2)];
+
+                (r, c, s, r1, c1, s1)
+            }
+        };
+
+        self.cube.add(r, c, s, 1);
+        self.cube.add(r, c1, s1, 1);
+        self.cube.add(r1, c, s1, 1);
+        self.cube.add(r1, c1, s, 1);
+        self.cube.add(r, c, s1, -1);
+        self.cube.add(r, c1, s, -1);
+        self.cube.add(r1, c, s, -1);
+        self.cube.add(r1, c1, s1, -1);
+
+        self.cube.improper_cell = (self.cube.get(r1, c1, s1) == -1).then_some((r1, c1, s1));
+    }
+}
+
+impl Iterator for UniformLatinSquareGenerator {
+    type Item = LatinSquareDyn;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // there is only one square of order 1, and no cell to move to
+        if self.cube.n == 1 {
+            return Some(self.cube.to_latin_square());
+        }
+
+        for _ in 0..self.mixing_steps {
+            self.step();
+        }
+
+        while self.cube.improper_cell.is_some() {
+            self.step();
+        }
+
+        Some(self.cube.to_latin_square())
+    }
+}
+
+/// A latin square as a 0-1 cube with a one at (row, col, val) for every entry.
+/// During the chain the square may become improper, which means there is exactly
+/// one cell with -1 and every line of the cube still sums to 1
+#[derive(Debug, Clone)]
+struct IncidenceCube {
+    n: usize,
+    cube: Box<[i8]>,
+    improper_cell: Option<(usize, usize, usize)>,
+}
+
+impl IncidenceCube {
+    fn cyclic(n: usize) -> Self {
+        let mut cube = IncidenceCube {
+            n,
+            cube: vec![0; n * n * n].into_boxed_slice(),
+            improper_cell: None,
+        };
+
+        for i in 0..n {
+            for j in 0..n {
+                cube.add(i, j, (i + j) % n, 1);
+            }
+        }
+
+        cube
+    }
+
+    fn get(&self, row: usize, col: usize, val: usize) -> i8 {
+        self.cube[(row * self.n + col) * self.n + val]
+    }
+
+    fn add(&mut self, row: usize, col: usize, val: usize, value: i8) {
+        self.cube[(row * self.n + col) * self.n + val] += value;
+    }
+
+    fn to_latin_square(&self) -> LatinSquareDyn {
+        debug_assert!(self.improper_cell.is_none());
+
+        let n = self.n;
+        let values = (0..n * n)
+            .map(|i| (0..n).find(|v| self.get(i / n, i % n, *v) == 1).unwrap() as u8)
+            .collect();
+
+        LatinSquareDyn::from_boxed_slice(values).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn order_1() {
+        let sqs: Vec<_> = UniformLatinSquareGenerator::new(1, 42, 1).take(3).collect();
+
+        assert_eq!(sqs, vec![LatinSquareDyn::try_from("0").unwrap(); 3]);
+    }
+
+    #[test]
+    fn uniform_order_3() {
+        let samples = 12000;
+
+        let mut counts = HashMap::new();
+        for sq in UniformLatinSquareGenerator::new(3, 42, 27).take(samples) {
+            *counts.entry(sq).or_insert(0usize) += 1;
+        }
+
+        assert_eq!(counts.len(), 12);
+
+        let expected = samples as f64 / 12.0;
+        let chi_square: f64 = counts
+            .values()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum();
+
+        // 99.9% quantile of the chi-square distribution with 11 degrees of freedom
+        assert!(chi_square < 31.3, "{chi_square}");
+    }
+}
@@ -0,0 +1,64 @@
+use std::{env, fs, process::Command};
+
+#[test]
+fn input_and_output_files() {
+    let dir = env::temp_dir();
+    let input = dir.join(format!("latin_squares_input_{}.txt", std::process::id()));
+    let output = dir.join(format!("latin_squares_output_{}.txt", std::process::id()));
+    fs::write(&input, "0123103223013210\n0123123023013012\n").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_latin_squares"))
+        .args(["count-transversals", "4", "--max-threads", "2", "--input"])
+        .arg(&input)
+        .arg("--output")
+        .arg(&output)
+        .status()
+        .unwrap();
+
+    let result = fs::read_to_string(&output).unwrap();
+    fs::remove_file(&input).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert!(status.success());
+    assert_eq!(result, "8\n0\n");
+}
+
+#[test]
+fn threaded_output_file() {
+    let output = env::temp_dir().join(format!(
+        "latin_squares_main_classes_{}.txt",
+        std::process::id()
+    ));
+
+    let stdout = Command::new(env!("CARGO_BIN_EXE_latin_squares"))
+        .args(["generate-main-classes", "6", "--max-threads", "4"])
+        .output()
+        .unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_latin_squares"))
+        .args([
+            "generate-main-classes",
+            "6",
+            "--max-threads",
+            "4",
+            "--output",
+        ])
+        .arg(&output)
+        .status()
+        .unwrap();
+
+    let result = fs::read_to_string(&output).unwrap();
+    fs::remove_file(&output).unwrap();
+
+    assert!(status.success());
+
+    let mut expected: Vec<_> = std::str::from_utf8(&stdout.stdout)
+        .unwrap()
+        .lines()
+        .collect();
+    let mut lines: Vec<_> = result.lines().collect();
+    expected.sort();
+    lines.sort();
+
+    assert_eq!(expected.len(), 12);
+    assert_eq!(lines, expected);
+}